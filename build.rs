@@ -0,0 +1,34 @@
+//! Picks the kernels vectorizing the set operations, exposed to the crate as
+//! the `kernel` cfg, from the enabled features and the target:
+//! - `scalar`: the `scalar-only` feature disables all the other kernels
+//! - `portable`: the `portable-simd` feature uses `std::simd` (nightly only)
+//! - `neon`: the `neon` feature on aarch64 with NEON enabled
+//! - `avx2`: the `avx2` feature on x86_64, the CPU support is checked at runtime
+//! - `default`: the SWAR kernels on the targets without a popcount instruction,
+//!   the scalar ones otherwise
+
+use std::env;
+
+fn main() {
+    println!(
+        "cargo::rustc-check-cfg=cfg(kernel, values(\"scalar\", \"portable\", \"neon\", \"avx2\", \"default\"))"
+    );
+
+    let feature = |name: &str| env::var_os(format!("CARGO_FEATURE_{name}")).is_some();
+    let arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+    let target_feature = |name: &str| target_features.split(',').any(|f| f == name);
+
+    let kernel = if feature("SCALAR_ONLY") {
+        "scalar"
+    } else if feature("PORTABLE_SIMD") {
+        "portable"
+    } else if feature("NEON") && arch == "aarch64" && target_feature("neon") {
+        "neon"
+    } else if feature("AVX2") && arch == "x86_64" {
+        "avx2"
+    } else {
+        "default"
+    };
+    println!("cargo::rustc-cfg=kernel=\"{kernel}\"");
+}
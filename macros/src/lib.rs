@@ -0,0 +1,124 @@
+//! The procedural macros of the `bitmap` crate, use them through its `macros` feature.
+
+use std::path::PathBuf;
+
+use proc_macro::{Literal, TokenStream, TokenTree};
+
+/// Number of `u64` words of a bitmap.
+const WORDS: usize = 1024;
+
+/// Builds a `Bitmap` at compile time from a text file, the expression is a
+/// constant thus it can initialize a `static`:
+///
+/// ```ignore
+/// use bitmap::{include_bitmap, Bitmap};
+///
+/// static ALLOWED: Bitmap = include_bitmap!("data/allowed_ids.txt");
+/// ```
+///
+/// The path is relative to the directory of the `Cargo.toml` of the crate
+/// calling the macro. The file holds values and inclusive ranges of values
+/// separated by commas or whitespaces, like `1-5, 12, 20-21`, which is the
+/// output of `Bitmap::write_ranges`. Everything following a `#` on a line is
+/// a comment.
+#[proc_macro]
+pub fn include_bitmap(input: TokenStream) -> TokenStream {
+    match expand(input) {
+        Ok(output) => output,
+        Err(error) => format!("::core::compile_error!({error:?})")
+            .parse()
+            .unwrap(),
+    }
+}
+
+fn expand(input: TokenStream) -> Result<TokenStream, String> {
+    let mut tokens = input.into_iter();
+    let path = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Literal(literal)), None) => string_literal(&literal)?,
+        _ => return Err("expected a single string literal, the path of the file".to_string()),
+    };
+
+    let manifest = std::env::var("CARGO_MANIFEST_DIR").map_err(|e| e.to_string())?;
+    let path = PathBuf::from(manifest).join(path);
+    let content = std::fs::read_to_string(&path)
+        .map_err(|e| format!("can't read {}: {e}", path.display()))?;
+    let words = parse(&content).map_err(|e| format!("{}:{e}", path.display()))?;
+
+    let words: Vec<String> = words.iter().map(|word| format!("{word:#x}")).collect();
+    // the file is included to rebuild the crate when it changes
+    Ok(format!(
+        "{{
+            const _: &[u8] = ::core::include_bytes!({path:?});
+            const BITMAP: ::bitmap::Bitmap = ::bitmap::Bitmap::from_words([{}]);
+            BITMAP
+        }}",
+        words.join(", "),
+        path = path.display().to_string(),
+    )
+    .parse()
+    .unwrap())
+}
+
+/// Returns the content of a plain string literal, the escapes aren't supported.
+fn string_literal(literal: &Literal) -> Result<String, String> {
+    let literal = literal.to_string();
+    literal
+        .strip_prefix('"')
+        .and_then(|literal| literal.strip_suffix('"'))
+        .filter(|content| !content.contains('\\'))
+        .map(str::to_string)
+        .ok_or_else(|| format!("expected a plain string literal, got {literal}"))
+}
+
+/// Parses the values of the file into the words of a bitmap.
+fn parse(content: &str) -> Result<[u64; WORDS], String> {
+    let mut words = [0; WORDS];
+    for (line_number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        for token in line.split(|c: char| c == ',' || c.is_whitespace()) {
+            if token.is_empty() {
+                continue;
+            }
+            let value = |value: &str| {
+                value.parse::<u16>().map_err(|_| {
+                    format!(
+                        "{}: `{token}` isn't a value nor a range of values in 0..=65535",
+                        line_number + 1
+                    )
+                })
+            };
+            let (start, end) = match token.split_once('-') {
+                Some((start, end)) => (value(start)?, value(end)?),
+                None => (value(token)?, value(token)?),
+            };
+            for value in start..=end {
+                words[value as usize / 64] |= 1 << (value % 64);
+            }
+        }
+    }
+    Ok(words)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_values() {
+        let words = parse("1, 3-4\n# 5\n\t64 65535 # 66").unwrap();
+        assert_eq!(words[0], 0b11010);
+        assert_eq!(words[1], 1);
+        assert_eq!(words[WORDS - 1], 1 << 63);
+        assert_eq!(words.iter().map(|word| word.count_ones()).sum::<u32>(), 5);
+        assert_eq!(parse("5-3").unwrap(), [0; WORDS]);
+
+        assert_eq!(
+            parse("1\n2, 65536").unwrap_err(),
+            "2: `65536` isn't a value nor a range of values in 0..=65535"
+        );
+        assert_eq!(
+            parse("1-2-3").unwrap_err(),
+            "1: `1-2-3` isn't a value nor a range of values in 0..=65535"
+        );
+    }
+}
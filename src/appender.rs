@@ -0,0 +1,142 @@
+use crate::ranges::range_masks;
+use crate::Bitmap;
+
+/// Builds a bitmap from a stream of booleans, the `i`-th boolean pushed
+/// tells if the value `i` is in the bitmap.
+///
+/// ```
+/// use bitmap::BitmapAppender;
+///
+/// let mut appender = BitmapAppender::new();
+/// appender.push(true);
+/// appender.push_run(false, 10);
+/// appender.push_run(true, 2);
+/// assert_eq!(appender.finish().to_vec(), [0, 11, 12]);
+/// ```
+#[derive(Clone, Default)]
+pub struct BitmapAppender {
+    bitmap: Bitmap,
+    /// The value associated with the next boolean pushed.
+    position: usize,
+}
+
+impl BitmapAppender {
+    pub fn new() -> Self {
+        BitmapAppender::default()
+    }
+
+    /// The number of booleans pushed so far, which is also the value
+    /// associated with the next one.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Appends a single boolean.
+    ///
+    /// # Panics
+    ///
+    /// If 65536 booleans were already pushed.
+    #[inline]
+    pub fn push(&mut self, bit: bool) {
+        self.push_run(bit, 1);
+    }
+
+    /// Appends `n` times the same boolean, whole words are filled at once.
+    ///
+    /// # Panics
+    ///
+    /// If it would push more than 65536 booleans in total.
+    pub fn push_run(&mut self, bit: bool, n: usize) {
+        let end = self.position.checked_add(n);
+        let Some(end) = end.filter(|end| *end <= u16::MAX as usize + 1) else {
+            panic!(
+                "a bitmap can't hold more than 65536 values, tried to push up to {}",
+                self.position as u128 + n as u128
+            );
+        };
+        if bit {
+            for (key, mask) in range_masks(self.position..end) {
+                self.bitmap.store[key] |= mask;
+            }
+            self.bitmap.len += n;
+        }
+        self.position = end;
+    }
+
+    /// Returns the bitmap built, the values that weren't pushed are absent.
+    pub fn finish(self) -> Bitmap {
+        self.bitmap.debug_assert_invariants();
+        self.bitmap
+    }
+}
+
+impl Extend<bool> for BitmapAppender {
+    fn extend<T: IntoIterator<Item = bool>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|bit| self.push(bit));
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn appender() {
+        let mut appender = BitmapAppender::new();
+        appender.extend([true, false, true]);
+        appender.push_run(false, 60);
+        appender.push_run(true, 3);
+        appender.push_run(true, 0);
+        assert_eq!(appender.position(), 66);
+        insta::assert_debug_snapshot!(appender.finish(), @r###"
+        {
+            0,
+            2,
+            63,
+            64,
+            65,
+        }
+        "###);
+
+        let mut appender = BitmapAppender::new();
+        appender.push_run(true, u16::MAX as usize + 1);
+        assert_eq!(appender.finish(), Bitmap::full());
+    }
+
+    #[test]
+    #[should_panic = "a bitmap can't hold more than 65536 values, tried to push up to 65537"]
+    fn overflow() {
+        let mut appender = BitmapAppender::new();
+        appender.push_run(false, u16::MAX as usize + 1);
+        appender.push(false);
+    }
+
+    #[test]
+    #[should_panic = "a bitmap can't hold more than 65536 values, tried to push up to 18446744073709551616"]
+    fn overflow_usize() {
+        let mut appender = BitmapAppender::new();
+        appender.push(true);
+        appender.push_run(true, usize::MAX);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_appender(runs in prop::collection::vec((any::<bool>(), 0..200_usize), 0..100)) {
+            let mut appender = BitmapAppender::new();
+            let mut expected = Bitmap::new();
+            for (bit, n) in runs {
+                for value in appender.position()..appender.position() + n {
+                    if bit {
+                        expected.insert(value as u16);
+                    }
+                }
+                appender.push_run(bit, n);
+            }
+            let bitmap = appender.finish();
+            bitmap.assert_invariants();
+            assert_eq!(bitmap, expected);
+        }
+    }
+}
@@ -0,0 +1,223 @@
+//! The bitmap seen as a 65536 bits unsigned integer: the value `v` is the bit
+//! of weight `2^v`, thus the first word of the store is the least significant one.
+
+use core::cmp::Ordering;
+
+use crate::generation::Generation;
+use crate::{Bitmap, Word};
+
+impl Bitmap {
+    /// Number of bits of the integer, and of values of the bitmap.
+    const BITS: u32 = u16::MAX as u32 + 1;
+
+    /// Builds a bitmap from its store and computes its length.
+    fn from_store(store: [Word; Self::BITMAP_SIZE]) -> Self {
+        let mut bitmap = Bitmap {
+            len: 0,
+            store,
+            generation: Generation::new(),
+        };
+        bitmap.recompute_len();
+        bitmap
+    }
+
+    /// Adds both bitmaps as integers, returns `None` on overflow.
+    pub fn checked_add(&self, other: &Self) -> Option<Self> {
+        let mut store = [0; Self::BITMAP_SIZE];
+        let mut carry = false;
+        for (ret, (left, right)) in store.iter_mut().zip(self.store.iter().zip(&other.store)) {
+            let (sum, overflow) = left.overflowing_add(*right);
+            let (sum, carried) = sum.overflowing_add(carry as Word);
+            *ret = sum;
+            carry = overflow || carried;
+        }
+        (!carry).then(|| Self::from_store(store))
+    }
+
+    /// Subtracts `other` from `self` as integers, returns `None` if `other`
+    /// is greater than `self`.
+    pub fn checked_sub(&self, other: &Self) -> Option<Self> {
+        let mut store = [0; Self::BITMAP_SIZE];
+        let mut borrow = false;
+        for (ret, (left, right)) in store.iter_mut().zip(self.store.iter().zip(&other.store)) {
+            let (diff, overflow) = left.overflowing_sub(*right);
+            let (diff, borrowed) = diff.overflowing_sub(borrow as Word);
+            *ret = diff;
+            borrow = overflow || borrowed;
+        }
+        (!borrow).then(|| Self::from_store(store))
+    }
+
+    /// Shifts the integer `n` bits to the left, the bits going past the most
+    /// significant one are dropped. As a set, `n` is added to every value and
+    /// the values greater than `u16::MAX` are dropped.
+    pub fn shl(&self, n: u32) -> Self {
+        if n >= Self::BITS {
+            return Bitmap::new();
+        }
+        let (words, bits) = ((n / Word::BITS) as usize, n % Word::BITS);
+        let mut store = [0; Self::BITMAP_SIZE];
+        for (source, word) in store[words..].iter_mut().enumerate() {
+            *word = self.store[source] << bits;
+            if bits != 0 && source > 0 {
+                *word |= self.store[source - 1] >> (Word::BITS - bits);
+            }
+        }
+        Self::from_store(store)
+    }
+
+    /// Shifts the integer `n` bits to the right, the bits going past the
+    /// least significant one are dropped. As a set, `n` is subtracted from
+    /// every value and the values lower than `n` are dropped.
+    pub fn shr(&self, n: u32) -> Self {
+        if n >= Self::BITS {
+            return Bitmap::new();
+        }
+        let mut store = [0; Self::BITMAP_SIZE];
+        for (key, word) in store.iter_mut().enumerate() {
+            *word = self.shr_word(key, n);
+        }
+        Self::from_store(store)
+    }
+
+    /// Returns the word `key` of the store shifted `n` bits to the right,
+    /// `n` being lower than [`Bitmap::BITS`].
+    #[inline]
+    fn shr_word(&self, key: usize, n: u32) -> Word {
+        let (words, bits) = ((n / Word::BITS) as usize, n % Word::BITS);
+        let source = key + words;
+        let Some(&word) = self.store.get(source) else {
+            return 0;
+        };
+        match self.store.get(source + 1) {
+            Some(next) if bits != 0 => word >> bits | next << (Word::BITS - bits),
+            _ => word >> bits,
+        }
+    }
+
+    /// Returns the positions where the phrase described by `positions` starts,
+    /// i.e. `positions[0] & positions[1].shr(1) & positions[2].shr(2) & ...`
+    /// where `positions[i]` holds the positions of the `i`-th term of the phrase.
+    ///
+    /// All the shifts and intersections are fused in a single pass over the
+    /// store, and the terms are skipped as soon as a word becomes empty.
+    /// Returns an empty bitmap if `positions` is empty.
+    /// ```
+    /// use bitmap::Bitmap;
+    ///
+    /// // "the quick brown fox jumps over the lazy dog"
+    /// let the = Bitmap::from_iter([0, 6]);
+    /// let lazy = Bitmap::from_iter([7]);
+    /// assert_eq!(Bitmap::phrase_match(&[&the, &lazy]).to_vec(), [6]);
+    /// ```
+    pub fn phrase_match(positions: &[&Bitmap]) -> Self {
+        let mut store = [0; Self::BITMAP_SIZE];
+        if positions.is_empty() || positions.len() > Self::BITS as usize {
+            return Self::from_store(store);
+        }
+        for (key, word) in store.iter_mut().enumerate() {
+            *word = Word::MAX;
+            for (n, term) in positions.iter().enumerate() {
+                *word &= term.shr_word(key, n as u32);
+                if *word == 0 {
+                    break;
+                }
+            }
+        }
+        Self::from_store(store)
+    }
+
+    /// Compares both bitmaps as integers. The bitmap holding the greatest
+    /// value absent from the other one is the greatest.
+    pub fn cmp_int(&self, other: &Self) -> Ordering {
+        self.store.iter().rev().cmp(other.store.iter().rev())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Builds a bitmap holding the bits of `value`.
+    fn int(value: u128) -> Bitmap {
+        Bitmap::from_iter((0..128).filter(|bit| value >> bit & 1 == 1))
+    }
+
+    #[test]
+    fn arithmetic() {
+        let max = Bitmap::full();
+        assert_eq!(max.checked_add(&int(0)), Some(Bitmap::full()));
+        assert_eq!(max.checked_add(&int(1)), None);
+        assert_eq!(int(0).checked_sub(&int(1)), None);
+        assert_eq!(max.checked_sub(&max), Some(Bitmap::new()));
+        // the carry goes through all the words
+        assert_eq!(
+            max.checked_sub(&int(1)).unwrap().checked_add(&int(1)),
+            Some(Bitmap::full())
+        );
+        assert_eq!(
+            Bitmap::from_iter([u16::MAX - 1]).checked_add(&Bitmap::from_iter([u16::MAX - 1])),
+            Some(Bitmap::from_iter([u16::MAX]))
+        );
+
+        assert_eq!(max.shl(1).to_vec()[0], 1);
+        assert_eq!(max.shl(65535).to_vec(), [u16::MAX]);
+        assert!(max.shl(65536).is_empty());
+        assert_eq!(max.shr(65535).to_vec(), [0]);
+        assert!(max.shr(u32::MAX).is_empty());
+        assert_eq!(int(1).shl(64), Bitmap::from_iter([64]));
+
+        let (a, b) = (
+            Bitmap::from_iter([0, 63, 200]),
+            Bitmap::from_iter([64, 201, 500]),
+        );
+        assert_eq!(Bitmap::phrase_match(&[&a, &b]).to_vec(), [63, 200]);
+        assert_eq!(Bitmap::phrase_match(&[&a, &b, &a]).to_vec(), []);
+        assert_eq!(Bitmap::phrase_match(&[&b]), b);
+        assert!(Bitmap::phrase_match(&[]).is_empty());
+        assert_eq!(Bitmap::phrase_match(&[&max, &max]).len(), 65535);
+
+        assert_eq!(int(3).cmp_int(&int(4)), Ordering::Less);
+        assert_eq!(max.cmp_int(&max), Ordering::Equal);
+        assert_eq!(
+            Bitmap::from_iter([u16::MAX]).cmp_int(&int(u128::MAX)),
+            Ordering::Greater
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_u128(left in any::<u128>(), right in any::<u128>(), n in 0..128_u32) {
+            assert_eq!(int(left).checked_add(&int(right)).unwrap(), Bitmap::from_iter(
+                (0..129).filter(|bit| {
+                    let (sum, carry) = left.overflowing_add(right);
+                    if *bit == 128 { carry } else { sum >> bit & 1 == 1 }
+                })
+            ));
+            assert_eq!(int(left).checked_sub(&int(right)), left.checked_sub(right).map(int));
+            assert_eq!(int(left).shr(n), int(left >> n));
+            assert_eq!(int(left).cmp_int(&int(right)), left.cmp(&right));
+        }
+
+        #[test]
+        fn prop_shift(bitmap in crate::strategies::any(), n in 0..70_000_u32) {
+            let shl = bitmap.shl(n);
+            let expected = Bitmap::from_iter(bitmap.iter().filter_map(|v| u16::try_from(v as u32 + n).ok()));
+            assert_eq!(shl, expected);
+            let shr = bitmap.shr(n);
+            let expected = Bitmap::from_iter(bitmap.iter().filter_map(|v| (v as u32).checked_sub(n).map(|v| v as u16)));
+            assert_eq!(shr, expected);
+        }
+
+        #[test]
+        fn prop_phrase_match(terms in prop::collection::vec(crate::strategies::any(), 1..4)) {
+            let mut expected = terms[0].clone();
+            for (n, term) in terms.iter().enumerate().skip(1) {
+                expected.intersection(&term.shr(n as u32));
+            }
+            let terms: Vec<&Bitmap> = terms.iter().collect();
+            assert_eq!(Bitmap::phrase_match(&terms), expected);
+        }
+    }
+}
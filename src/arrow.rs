@@ -0,0 +1,144 @@
+//! Conversions from and to the validity bitmaps of [Apache Arrow](https://arrow.apache.org/docs/format/Columnar.html#validity-bitmaps).
+//!
+//! Arrow packs the validity of the rows in bytes, least significant bit first:
+//! the row `i` is valid if the bit `i % 8` of the byte `i / 8` is set.
+//! Since the words of the store follow the same order once they're written in
+//! little endian, the conversion is a plain copy of bytes on every platform.
+
+use crate::{Bitmap, Word};
+
+impl Bitmap {
+    /// Number of bytes of a validity buffer covering the whole universe.
+    pub const VALIDITY_BUFFER_SIZE: usize = Self::RAW_SIZE;
+
+    /// Creates a bitmap from an Arrow validity buffer, the value `i` is
+    /// present if the row `i` is valid.
+    ///
+    /// Only the first `rows` bits of the buffer are read, the padding bits of
+    /// the last byte are ignored.
+    ///
+    /// # Panics
+    ///
+    /// If `rows` is greater than the number of bits of `buffer` or than 65536.
+    pub fn from_validity_buffer(buffer: &[u8], rows: usize) -> Self {
+        assert!(
+            rows <= buffer.len() * 8,
+            "the validity buffer is too small to hold {rows} rows"
+        );
+        assert!(
+            rows <= u16::MAX as usize + 1,
+            "a bitmap can't hold more than 65536 rows, got {rows}"
+        );
+        let buffer = &buffer[..rows.div_ceil(8)];
+
+        let mut bitmap = Bitmap::new();
+        let word_size = core::mem::size_of::<Word>();
+        for (word, bytes) in bitmap.store.iter_mut().zip(buffer.chunks(word_size)) {
+            let mut le = [0; core::mem::size_of::<Word>()];
+            le[..bytes.len()].copy_from_slice(bytes);
+            *word = Word::from_le_bytes(le);
+        }
+        // clear the padding bits of the last word
+        if !rows.is_multiple_of(Word::BITS as usize) {
+            bitmap.store[rows / Word::BITS as usize] &= (1 << (rows % Word::BITS as usize)) - 1;
+        }
+        bitmap.recompute_len();
+        bitmap
+    }
+
+    /// Writes the validity buffer of `rows` rows where the row `i` is valid if
+    /// the value `i` is in the bitmap. The values greater or equal to `rows` are ignored.
+    ///
+    /// The returned buffer is `rows.div_ceil(8)` bytes long, the padding bits are unset.
+    ///
+    /// # Panics
+    ///
+    /// If `rows` is greater than 65536.
+    pub fn to_validity_buffer(&self, rows: usize) -> Vec<u8> {
+        assert!(
+            rows <= u16::MAX as usize + 1,
+            "a bitmap can't hold more than 65536 rows, got {rows}"
+        );
+        let mut buffer: Vec<u8> = self
+            .store
+            .iter()
+            .flat_map(|word| word.to_le_bytes())
+            .take(rows.div_ceil(8))
+            .collect();
+        if !rows.is_multiple_of(8) {
+            *buffer.last_mut().unwrap() &= (1 << (rows % 8)) - 1;
+        }
+        buffer
+    }
+
+    /// Returns the number of null rows among the first `rows` rows, i.e. the
+    /// number of values lower than `rows` missing from the bitmap, as expected
+    /// by the `null_count` of an Arrow array.
+    pub fn null_count(&self, rows: usize) -> usize {
+        let rows = rows.min(u16::MAX as usize + 1);
+        let full_words = rows / Word::BITS as usize;
+        let mut valid: usize = self.store[..full_words]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        if !rows.is_multiple_of(Word::BITS as usize) {
+            let mask = (1 << (rows % Word::BITS as usize)) - 1;
+            valid += (self.store[full_words] & mask).count_ones() as usize;
+        }
+        rows - valid
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn validity_buffer() {
+        // rows 0, 2, 3 and 9 are valid, the padding of the last byte is garbage
+        let buffer = [0b0000_1101, 0b1111_1110];
+        let bitmap = Bitmap::from_validity_buffer(&buffer, 10);
+        insta::assert_debug_snapshot!(bitmap, @r###"
+        {
+            0,
+            2,
+            3,
+            9,
+        }
+        "###);
+        assert_eq!(bitmap.null_count(10), 6);
+        assert_eq!(bitmap.to_validity_buffer(10), [0b0000_1101, 0b0000_0010]);
+        assert_eq!(bitmap.to_validity_buffer(3), [0b0000_0101]);
+        assert!(bitmap.to_validity_buffer(0).is_empty());
+    }
+
+    #[test]
+    fn full_universe() {
+        let buffer = Bitmap::full().to_validity_buffer(u16::MAX as usize + 1);
+        assert_eq!(buffer.len(), Bitmap::VALIDITY_BUFFER_SIZE);
+        let bitmap = Bitmap::from_validity_buffer(&buffer, u16::MAX as usize + 1);
+        assert_eq!(bitmap, Bitmap::full());
+        assert_eq!(bitmap.null_count(u16::MAX as usize + 1), 0);
+    }
+
+    #[test]
+    #[should_panic = "the validity buffer is too small to hold 17 rows"]
+    fn buffer_too_small() {
+        Bitmap::from_validity_buffer(&[0, 0], 17);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_roundtrip(values in prop::collection::vec(0..=u16::MAX, 0..150), rows in 0..=u16::MAX as usize + 1) {
+            let bitmap = Bitmap::from_iter(&values);
+            let buffer = bitmap.to_validity_buffer(rows);
+            assert_eq!(buffer.len(), rows.div_ceil(8));
+
+            let expected = Bitmap::from_iter(values.iter().filter(|v| (**v as usize) < rows));
+            let decoded = Bitmap::from_validity_buffer(&buffer, rows);
+            assert_eq!(decoded, expected);
+            assert_eq!(bitmap.null_count(rows), rows - expected.len());
+        }
+    }
+}
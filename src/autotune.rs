@@ -0,0 +1,91 @@
+//! Picks the fastest kernel of every operation on the running machine, since
+//! the vectorized kernels aren't always faster than the scalar ones depending
+//! on the core. The kernels are timed once, on the first call of the operation.
+
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crate::backend::Op;
+use crate::Bitmap;
+
+/// The implementations available for an operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Kernel {
+    Scalar,
+    Swar,
+    /// The kernel selected by the features of the crate, see `build.rs`.
+    Vectorized,
+}
+
+const KERNELS: [Kernel; 3] = [Kernel::Scalar, Kernel::Swar, Kernel::Vectorized];
+
+/// Number of times a kernel is timed, only the fastest run is kept to ignore
+/// the preemptions and the cold caches.
+const RUNS: usize = 5;
+/// Number of calls of the kernel timed in a single run.
+const CALLS: usize = 16;
+
+/// Returns the duration of the fastest run of `f`.
+fn time(mut f: impl FnMut()) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            for _ in 0..CALLS {
+                f();
+            }
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+/// Returns the kernel of `op` used by [`Bitmap::intersection_simd`] and its siblings.
+pub(crate) fn kernel(op: Op) -> Kernel {
+    static KERNELS_BY_OP: [OnceLock<Kernel>; Op::ALL.len()] =
+        [const { OnceLock::new() }; Op::ALL.len()];
+    *KERNELS_BY_OP[op as usize].get_or_init(|| {
+        let left = Bitmap::from_iter((0..=u16::MAX).step_by(3));
+        let right = Bitmap::from_iter((0..=u16::MAX).step_by(5));
+        let mut scratch = left.clone();
+        KERNELS
+            .into_iter()
+            .min_by_key(|kernel| {
+                time(|| {
+                    scratch.clone_from(&left);
+                    match kernel {
+                        Kernel::Scalar => {
+                            scratch.binary_with(black_box(&right), op, crate::backend::Scalar)
+                        }
+                        Kernel::Swar => {
+                            scratch.binary_with(black_box(&right), op, crate::swar::Swar)
+                        }
+                        Kernel::Vectorized => scratch.binary_vectorized(black_box(&right), op),
+                    }
+                    black_box(&scratch);
+                })
+            })
+            .unwrap()
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn autotune() {
+        for op in Op::ALL {
+            let selected = kernel(op);
+            assert!(KERNELS.contains(&selected));
+            // the choice is made only once
+            assert_eq!(kernel(op), selected);
+        }
+
+        let mut bitmap = Bitmap::from_iter([0, 1, 63, 64, u16::MAX]);
+        bitmap.intersection_simd(&Bitmap::from_iter([1, 64, 1000]));
+        assert_eq!(bitmap.to_vec(), [1, 64]);
+        bitmap.union_simd(&Bitmap::from_iter([0, 64]));
+        assert_eq!(bitmap.to_vec(), [0, 1, 64]);
+    }
+}
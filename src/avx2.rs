@@ -0,0 +1,109 @@
+//! The kernels vectorized with the AVX2 instructions of x86_64. The CPU support
+//! is checked at runtime before calling them.
+
+use core::arch::x86_64::*;
+
+use crate::backend::{Backend, Op};
+use crate::{Bitmap, Word};
+
+/// Number of words held by an AVX2 register.
+const LANE_WORDS: usize = 4;
+
+/// The words loaded in or stored from a single register.
+type Chunk = [Word; LANE_WORDS];
+
+/// An AVX2 register holding a [`Chunk`] of words.
+#[derive(Clone, Copy)]
+pub(crate) struct Lane(__m256i);
+
+/// The backend of the x86_64 CPUs supporting AVX2 and POPCNT, it can only be
+/// built once the support was detected.
+#[derive(Clone, Copy)]
+pub(crate) struct Avx2(());
+
+impl Avx2 {
+    /// Returns the backend if the running CPU supports the instructions used by the kernels.
+    #[inline]
+    pub(crate) fn detect() -> Option<Self> {
+        let supported = std::arch::is_x86_feature_detected!("avx2")
+            && std::arch::is_x86_feature_detected!("popcnt");
+        supported.then_some(Avx2(()))
+    }
+
+    /// Compiles the generic drivers with AVX2 enabled.
+    #[inline]
+    pub(crate) fn binary(self, left: &mut Bitmap, right: &Bitmap, op: Op) {
+        #[target_feature(enable = "avx2,popcnt")]
+        fn binary(left: &mut Bitmap, right: &Bitmap, op: Op, backend: Avx2) {
+            left.binary_with(right, op, backend);
+        }
+        // SAFETY: the backend proves the CPU supports AVX2 and POPCNT.
+        unsafe { binary(left, right, op, self) }
+    }
+
+    #[inline]
+    pub(crate) fn complement(self, bitmap: &mut Bitmap) {
+        #[target_feature(enable = "avx2,popcnt")]
+        fn complement(bitmap: &mut Bitmap, backend: Avx2) {
+            bitmap.complement_with(backend);
+        }
+        // SAFETY: the backend proves the CPU supports AVX2 and POPCNT.
+        unsafe { complement(bitmap, self) }
+    }
+}
+
+// SAFETY (for all the intrinsics below): an `Avx2` backend proves the CPU supports AVX2.
+impl Backend for Avx2 {
+    type Lane = Lane;
+
+    const LANE_WORDS: usize = LANE_WORDS;
+
+    #[inline(always)]
+    fn load(self, words: &[Word]) -> Lane {
+        let chunk: &Chunk = words.try_into().unwrap();
+        // the reference is valid for reads of 32 bytes, the load is unaligned
+        Lane(unsafe { _mm256_loadu_si256(chunk.as_ptr().cast()) })
+    }
+
+    #[inline(always)]
+    fn store(self, lane: Lane, words: &mut [Word]) {
+        let chunk: &mut Chunk = words.try_into().unwrap();
+        // the reference is valid for writes of 32 bytes, the store is unaligned
+        unsafe { _mm256_storeu_si256(chunk.as_mut_ptr().cast(), lane.0) }
+    }
+
+    #[inline(always)]
+    fn and(self, left: Lane, right: Lane) -> Lane {
+        Lane(unsafe { _mm256_and_si256(left.0, right.0) })
+    }
+
+    #[inline(always)]
+    fn or(self, left: Lane, right: Lane) -> Lane {
+        Lane(unsafe { _mm256_or_si256(left.0, right.0) })
+    }
+
+    #[inline(always)]
+    fn and_not(self, left: Lane, right: Lane) -> Lane {
+        // the intrinsic negates its first operand
+        Lane(unsafe { _mm256_andnot_si256(right.0, left.0) })
+    }
+
+    #[inline(always)]
+    fn xor(self, left: Lane, right: Lane) -> Lane {
+        Lane(unsafe { _mm256_xor_si256(left.0, right.0) })
+    }
+
+    #[inline(always)]
+    fn not(self, lane: Lane) -> Lane {
+        // there is no NOT instruction, the lane is xored with all ones
+        Lane(unsafe { _mm256_xor_si256(lane.0, _mm256_set1_epi64x(-1)) })
+    }
+
+    #[inline(always)]
+    fn count_ones(self, lane: Lane) -> usize {
+        // AVX2 can't count the bits of a register, the scalar popcount is faster
+        let mut chunk: Chunk = [0; LANE_WORDS];
+        self.store(lane, &mut chunk);
+        chunk.iter().map(|word| word.count_ones() as usize).sum()
+    }
+}
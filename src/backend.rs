@@ -0,0 +1,280 @@
+//! The set operations are written once as generic drivers over a [`Backend`],
+//! which only knows how to load, store and combine a lane of words. Every
+//! vectorized module provides its own backend, selected by the `kernel` cfg,
+//! and the scalar one below is used everywhere else.
+
+use crate::{Bitmap, Word};
+
+/// The word kernels of a target. A backend is a token: the backends relying on
+/// instructions checked at runtime can only be built once they were detected.
+pub(crate) trait Backend: Copy {
+    /// A register holding [`Backend::LANE_WORDS`] words.
+    type Lane: Copy;
+
+    /// Number of words of a lane, the store always holds a whole number of lanes.
+    const LANE_WORDS: usize;
+
+    /// Loads exactly [`Backend::LANE_WORDS`] words.
+    fn load(self, words: &[Word]) -> Self::Lane;
+
+    /// Stores a lane in exactly [`Backend::LANE_WORDS`] words.
+    fn store(self, lane: Self::Lane, words: &mut [Word]);
+
+    fn and(self, left: Self::Lane, right: Self::Lane) -> Self::Lane;
+
+    fn or(self, left: Self::Lane, right: Self::Lane) -> Self::Lane;
+
+    /// Returns `left & !right`.
+    fn and_not(self, left: Self::Lane, right: Self::Lane) -> Self::Lane;
+
+    fn xor(self, left: Self::Lane, right: Self::Lane) -> Self::Lane;
+
+    fn not(self, lane: Self::Lane) -> Self::Lane;
+
+    fn count_ones(self, lane: Self::Lane) -> usize;
+}
+
+/// The binary operations provided by every backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Op {
+    And,
+    Or,
+    AndNot,
+    Xor,
+}
+
+impl Op {
+    #[cfg(any(test, feature = "autotune"))]
+    pub(crate) const ALL: [Op; 4] = [Op::And, Op::Or, Op::AndNot, Op::Xor];
+}
+
+/// The backend working on a single word at a time.
+#[derive(Clone, Copy)]
+pub(crate) struct Scalar;
+
+impl Backend for Scalar {
+    type Lane = Word;
+
+    const LANE_WORDS: usize = 1;
+
+    #[inline(always)]
+    fn load(self, words: &[Word]) -> Word {
+        words[0]
+    }
+
+    #[inline(always)]
+    fn store(self, lane: Word, words: &mut [Word]) {
+        words[0] = lane;
+    }
+
+    #[inline(always)]
+    fn and(self, left: Word, right: Word) -> Word {
+        left & right
+    }
+
+    #[inline(always)]
+    fn or(self, left: Word, right: Word) -> Word {
+        left | right
+    }
+
+    #[inline(always)]
+    fn and_not(self, left: Word, right: Word) -> Word {
+        left & !right
+    }
+
+    #[inline(always)]
+    fn xor(self, left: Word, right: Word) -> Word {
+        left ^ right
+    }
+
+    #[inline(always)]
+    fn not(self, lane: Word) -> Word {
+        !lane
+    }
+
+    #[inline(always)]
+    fn count_ones(self, lane: Word) -> usize {
+        lane.count_ones() as usize
+    }
+}
+
+/// Writes `op(left, right)` in `left` lane by lane and returns the number of
+/// bits set in the result.
+#[inline(always)]
+pub(crate) fn binary<B: Backend>(
+    backend: B,
+    left: &mut [Word; Bitmap::BITMAP_SIZE],
+    right: &[Word; Bitmap::BITMAP_SIZE],
+    op: impl Fn(B::Lane, B::Lane) -> B::Lane,
+) -> usize {
+    let mut count = 0;
+    for (left, right) in left
+        .chunks_exact_mut(B::LANE_WORDS)
+        .zip(right.chunks_exact(B::LANE_WORDS))
+    {
+        let lane = op(backend.load(left), backend.load(right));
+        backend.store(lane, left);
+        count += backend.count_ones(lane);
+    }
+    count
+}
+
+/// Writes `op(words)` in `words` lane by lane, without counting the bits.
+#[inline(always)]
+pub(crate) fn unary<B: Backend>(
+    backend: B,
+    words: &mut [Word; Bitmap::BITMAP_SIZE],
+    op: impl Fn(B::Lane) -> B::Lane,
+) {
+    for words in words.chunks_exact_mut(B::LANE_WORDS) {
+        let lane = op(backend.load(words));
+        backend.store(lane, words);
+    }
+}
+
+impl Bitmap {
+    /// Flips every word with `backend`, the length is derived from the previous one.
+    #[inline(always)]
+    pub(crate) fn complement_with<B: Backend>(&mut self, backend: B) {
+        unary(backend, &mut self.store, |lane| backend.not(lane));
+        self.len = u16::MAX as usize + 1 - self.len;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Writes `op(self, other)` in `self` with `backend`. The operation is
+    /// matched once, every arm is its own loop.
+    #[inline(always)]
+    pub(crate) fn binary_with<B: Backend>(&mut self, other: &Self, op: Op, backend: B) {
+        let (left, right) = (&mut self.store, &other.store);
+        self.len = match op {
+            Op::And => binary(backend, left, right, |l, r| backend.and(l, r)),
+            Op::Or => binary(backend, left, right, |l, r| backend.or(l, r)),
+            Op::AndNot => binary(backend, left, right, |l, r| backend.and_not(l, r)),
+            Op::Xor => binary(backend, left, right, |l, r| backend.xor(l, r)),
+        };
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    #[inline(always)]
+    pub(crate) fn intersection_with<B: Backend>(&mut self, other: &Self, backend: B) {
+        self.binary_with(other, Op::And, backend);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// Runs `check` with every backend available on the running machine.
+    fn for_each_backend(op: Op, mut check: impl FnMut(&str, &mut dyn FnMut(&mut Bitmap, &Bitmap))) {
+        check("scalar", &mut |left, right| {
+            left.binary_with(right, op, Scalar)
+        });
+        check("swar", &mut |left, right| {
+            left.binary_with(right, op, crate::swar::Swar)
+        });
+        #[cfg(kernel = "neon")]
+        check("neon", &mut |left, right| {
+            left.binary_with(right, op, crate::neon::Neon)
+        });
+        #[cfg(kernel = "portable")]
+        check("portable", &mut |left, right| {
+            left.binary_with(right, op, crate::portable::Portable)
+        });
+        #[cfg(kernel = "avx2")]
+        if let Some(avx2) = crate::avx2::Avx2::detect() {
+            check("avx2", &mut |left, right| avx2.binary(left, right, op));
+        }
+    }
+
+    fn check_binary(left: &Bitmap, right: &Bitmap) {
+        for op in Op::ALL {
+            let expected: Vec<Word> = left
+                .store
+                .iter()
+                .zip(&right.store)
+                .map(|(left, right)| match op {
+                    Op::And => left & right,
+                    Op::Or => left | right,
+                    Op::AndNot => left & !right,
+                    Op::Xor => left ^ right,
+                })
+                .collect();
+            for_each_backend(op, |name, kernel| {
+                let mut bitmap = left.clone();
+                kernel(&mut bitmap, right);
+                assert_eq!(bitmap.store.as_slice(), expected, "backend {name}, {op:?}");
+                bitmap.assert_invariants();
+            });
+        }
+    }
+
+    #[test]
+    fn backends() {
+        // every pair of interesting words at every position of a lane
+        let words = [
+            0,
+            1,
+            1 << 63,
+            Word::MAX,
+            0x5555_5555_5555_5555,
+            0xF0F0_F0F0_0F0F_0F0F,
+        ];
+        for (i, left) in words.iter().enumerate() {
+            for (j, right) in words.iter().enumerate() {
+                let mut l = Bitmap::new();
+                let mut r = Bitmap::new();
+                for key in 0..Bitmap::BITMAP_SIZE {
+                    l.store[key] = words[(i + key) % words.len()].rotate_left(key as u32) | left;
+                    r.store[key] = words[(j + key / 3) % words.len()] ^ right;
+                }
+                l.recompute_len();
+                r.recompute_len();
+                check_binary(&l, &r);
+            }
+        }
+        check_binary(&Bitmap::full(), &Bitmap::full());
+        check_binary(&Bitmap::new(), &Bitmap::full());
+    }
+
+    /// Runs `check` with the complement of every backend available on the running machine.
+    fn for_each_complement(mut check: impl FnMut(&str, &mut dyn FnMut(&mut Bitmap))) {
+        check("scalar", &mut |bitmap| bitmap.complement_with(Scalar));
+        check("swar", &mut |bitmap| {
+            bitmap.complement_with(crate::swar::Swar)
+        });
+        #[cfg(kernel = "neon")]
+        check("neon", &mut |bitmap| {
+            bitmap.complement_with(crate::neon::Neon)
+        });
+        #[cfg(kernel = "portable")]
+        check("portable", &mut |bitmap| {
+            bitmap.complement_with(crate::portable::Portable)
+        });
+        #[cfg(kernel = "avx2")]
+        if let Some(avx2) = crate::avx2::Avx2::detect() {
+            check("avx2", &mut |bitmap| avx2.complement(bitmap));
+        }
+    }
+
+    fn check_complement(bitmap: &Bitmap) {
+        let expected: Vec<Word> = bitmap.store.iter().map(|word| !word).collect();
+        for_each_complement(|name, complement| {
+            let mut complemented = bitmap.clone();
+            complement(&mut complemented);
+            assert_eq!(complemented.store.as_slice(), expected, "backend {name}");
+            complemented.assert_invariants();
+        });
+    }
+
+    proptest! {
+        #[test]
+        fn prop_backends(left in crate::strategies::any(), right in crate::strategies::any()) {
+            check_binary(&left, &right);
+            check_complement(&left);
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use core::fmt;
+
+use crate::{Bitmap, Word};
+
+/// A mutable reference to the bit of a value in a bitmap, see [`Bitmap::bit`].
+///
+/// The bit is updated in place and the length of the bitmap is only fixed once
+/// the reference is dropped.
+pub struct BitRef<'a> {
+    bitmap: &'a mut Bitmap,
+    value: u16,
+    /// Whether the value was in the bitmap when the reference was created.
+    was_set: bool,
+}
+
+impl BitRef<'_> {
+    #[inline]
+    fn word(&mut self) -> &mut Word {
+        &mut self.bitmap.store[Bitmap::key(self.value)]
+    }
+
+    #[inline]
+    fn mask(&self) -> Word {
+        1 << Bitmap::bit_offset(self.value)
+    }
+
+    /// The value the bit corresponds to.
+    #[inline]
+    pub fn value(&self) -> u16 {
+        self.value
+    }
+
+    /// Returns `true` if the value is currently in the bitmap.
+    #[inline]
+    pub fn get(&self) -> bool {
+        self.bitmap.contains(self.value)
+    }
+
+    /// Inserts the value in the bitmap.
+    #[inline]
+    pub fn set(&mut self) {
+        let mask = self.mask();
+        *self.word() |= mask;
+    }
+
+    /// Removes the value from the bitmap.
+    #[inline]
+    pub fn clear(&mut self) {
+        let mask = self.mask();
+        *self.word() &= !mask;
+    }
+
+    /// Inserts the value if it was absent, removes it otherwise.
+    #[inline]
+    pub fn toggle(&mut self) {
+        let mask = self.mask();
+        *self.word() ^= mask;
+    }
+
+    /// Inserts the value if `present` is `true`, removes it otherwise.
+    #[inline]
+    pub fn assign(&mut self, present: bool) {
+        if present {
+            self.set()
+        } else {
+            self.clear()
+        }
+    }
+}
+
+impl Drop for BitRef<'_> {
+    #[inline]
+    fn drop(&mut self) {
+        match (self.was_set, self.get()) {
+            (false, true) => self.bitmap.len += 1,
+            (true, false) => self.bitmap.len -= 1,
+            _ => (),
+        }
+        self.bitmap.generation.bump();
+        self.bitmap.debug_assert_invariants();
+    }
+}
+
+impl fmt::Debug for BitRef<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("BitRef")
+            .field("value", &self.value)
+            .field("set", &self.get())
+            .finish()
+    }
+}
+
+impl Bitmap {
+    /// Returns a mutable reference to the bit of `value`:
+    /// ```
+    /// use bitmap::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::new();
+    /// bitmap.bit(12).set();
+    /// bitmap.bit(13).toggle();
+    /// bitmap.bit(13).toggle();
+    /// assert_eq!(bitmap.to_vec(), [12]);
+    /// ```
+    #[inline]
+    pub fn bit(&mut self, value: u16) -> BitRef<'_> {
+        let was_set = self.contains(value);
+        BitRef {
+            bitmap: self,
+            value,
+            was_set,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn bit_ref() {
+        let mut bitmap = Bitmap::from_iter([0, 64]);
+        {
+            let mut bit = bitmap.bit(u16::MAX);
+            assert!(!bit.get());
+            bit.set();
+            bit.set();
+            assert!(bit.get());
+            assert_eq!(bit.value(), u16::MAX);
+            insta::assert_snapshot!(format!("{bit:?}"), @"BitRef { value: 65535, set: true }");
+        }
+        assert_eq!(bitmap.len(), 3);
+
+        bitmap.bit(0).clear();
+        bitmap.bit(64).toggle();
+        bitmap.bit(1).assign(true);
+        {
+            let mut bit = bitmap.bit(2);
+            bit.set();
+            bit.clear();
+        }
+        bitmap.assert_invariants();
+        assert_eq!(bitmap.to_vec(), [1, u16::MAX]);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bit_ref(bitmap in crate::strategies::any(), ops in prop::collection::vec((0..4_u8, any::<u16>()), 0..50)) {
+            let mut bitmap = bitmap;
+            let mut expected = bitmap.clone();
+            for (op, value) in ops {
+                let mut bit = bitmap.bit(value);
+                match op {
+                    0 => { bit.set(); expected.insert(value); }
+                    1 => { bit.clear(); expected.remove(value); }
+                    2 => {
+                        bit.toggle();
+                        if !expected.remove(value) {
+                            expected.insert(value);
+                        }
+                    }
+                    _ => assert_eq!(bit.get(), expected.contains(value)),
+                }
+            }
+            bitmap.assert_invariants();
+            assert_eq!(bitmap, expected);
+        }
+    }
+}
@@ -0,0 +1,158 @@
+use crate::generation::Generation;
+use crate::{Bitmap, Word};
+
+/// The words of a row, aligned on a cache line.
+#[derive(Clone)]
+#[repr(C, align(64))]
+struct Row([Word; Bitmap::BITMAP_SIZE]);
+
+/// Many bitmaps stored contiguously, one aligned row of words per bitmap and
+/// their lengths on the side, to run a query against all of them at once.
+///
+/// The bulk operations are blocked: a block of the query is loaded once and
+/// checked against every row before moving to the next block, and the empty
+/// blocks of the query are skipped for all the rows.
+#[derive(Clone, Default)]
+pub struct BitmapVec {
+    rows: Vec<Row>,
+    lens: Vec<usize>,
+}
+
+impl BitmapVec {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        BitmapVec {
+            rows: Vec::with_capacity(capacity),
+            lens: Vec::with_capacity(capacity),
+        }
+    }
+
+    /// Number of bitmaps in the collection.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Append a copy of `bitmap` and returns its position.
+    pub fn push(&mut self, bitmap: &Bitmap) -> usize {
+        self.rows.push(Row(bitmap.store));
+        self.lens.push(bitmap.len());
+        self.rows.len() - 1
+    }
+
+    /// Returns a copy of the bitmap at position `i`.
+    pub fn get(&self, i: usize) -> Option<Bitmap> {
+        Some(Bitmap {
+            len: *self.lens.get(i)?,
+            store: self.rows[i].0,
+            generation: Generation::new(),
+        })
+    }
+
+    /// Returns the length of the bitmap at position `i` without copying it.
+    #[inline]
+    pub fn row_len(&self, i: usize) -> Option<usize> {
+        self.lens.get(i).copied()
+    }
+
+    /// Returns the number of values present in both `query` and every
+    /// bitmap of the collection, in the same order.
+    pub fn intersection_lens(&self, query: &Bitmap) -> Vec<usize> {
+        Bitmap::blocked_intersection_lens(&query.store, self.rows.iter().map(|row| &row.0))
+    }
+
+    /// Returns the positions of the bitmaps sharing at least `min_len` values
+    /// with `query`, in increasing order.
+    pub fn filter(&self, query: &Bitmap, min_len: usize) -> Vec<usize> {
+        self.intersection_lens(query)
+            .into_iter()
+            .enumerate()
+            .filter(|(_, len)| *len >= min_len)
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Computes the intersection of `query` with every bitmap of the collection in place.
+    pub fn intersect_all(&mut self, query: &Bitmap) {
+        for (row, len) in self.rows.iter_mut().zip(&mut self.lens) {
+            let mut count = 0;
+            for (word, query) in row.0.iter_mut().zip(&query.store) {
+                *word &= query;
+                count += word.count_ones() as usize;
+            }
+            *len = count;
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a Bitmap> for BitmapVec {
+    fn from_iter<T: IntoIterator<Item = &'a Bitmap>>(iter: T) -> Self {
+        let mut vec = BitmapVec::new();
+        iter.into_iter().for_each(|bitmap| {
+            vec.push(bitmap);
+        });
+        vec
+    }
+}
+
+impl FromIterator<Bitmap> for BitmapVec {
+    fn from_iter<T: IntoIterator<Item = Bitmap>>(iter: T) -> Self {
+        let mut vec = BitmapVec::new();
+        iter.into_iter().for_each(|bitmap| {
+            vec.push(&bitmap);
+        });
+        vec
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn bitmap_vec() {
+        let mut vec = BitmapVec::new();
+        assert!(vec.is_empty());
+        assert_eq!(vec.push(&Bitmap::from_iter([1, 2, 3])), 0);
+        assert_eq!(vec.push(&Bitmap::from_iter([3, u16::MAX])), 1);
+        vec.push(&Bitmap::new());
+        assert_eq!(vec.len(), 3);
+        assert_eq!(vec.row_len(1), Some(2));
+        assert_eq!(vec.get(1).unwrap().to_vec(), [3, u16::MAX]);
+        assert!(vec.get(3).is_none());
+        assert_eq!(vec.rows.as_ptr() as usize % 64, 0);
+
+        let query = Bitmap::from_iter([2, 3, u16::MAX]);
+        assert_eq!(vec.intersection_lens(&query), [2, 2, 0]);
+        assert_eq!(vec.filter(&query, 1), [0, 1]);
+        vec.intersect_all(&Bitmap::from_iter([u16::MAX]));
+        assert_eq!(vec.row_len(0), Some(0));
+        assert_eq!(vec.get(1).unwrap().to_vec(), [u16::MAX]);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bitmap_vec(query in crate::strategies::any(), bitmaps in prop::collection::vec(crate::strategies::any(), 0..5)) {
+            let mut vec = BitmapVec::from_iter(&bitmaps);
+            let expected: Vec<usize> = bitmaps.iter().map(|bitmap| (bitmap.clone() & &query).len()).collect();
+            assert_eq!(vec.intersection_lens(&query), expected);
+            assert_eq!(vec.intersection_lens(&query), query.intersection_len_many(&bitmaps.iter().collect::<Vec<_>>()));
+
+            vec.intersect_all(&query);
+            for (i, bitmap) in bitmaps.iter().enumerate() {
+                let row = vec.get(i).unwrap();
+                row.assert_invariants();
+                assert_eq!(row, bitmap.clone() & &query);
+            }
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use crate::{Bitmap, Error};
+
+impl Bitmap {
+    /// Insert a wider integer in the bitmap.
+    /// Returns `true` if the value was not already present in the bitmap, and
+    /// [`Error::OutOfRange`] if it doesn't fit in a `u16`.
+    #[inline]
+    pub fn try_insert(&mut self, value: u32) -> Result<bool, Error> {
+        let value = u16::try_from(value).map_err(|_| Error::OutOfRange(value))?;
+        Ok(self.insert(value))
+    }
+
+    /// Creates a bitmap from wider integers, stops at the first value that
+    /// doesn't fit in a `u16`.
+    pub fn from_iter_checked(iter: impl IntoIterator<Item = u32>) -> Result<Self, Error> {
+        let mut bitmap = Bitmap::new();
+        for value in iter {
+            bitmap.try_insert(value)?;
+        }
+        Ok(bitmap)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn checked() {
+        let mut bitmap = Bitmap::from_iter_checked([0, 12, u16::MAX as u32]).unwrap();
+        assert_eq!(bitmap.try_insert(12), Ok(false));
+        assert_eq!(bitmap.try_insert(13), Ok(true));
+        assert_eq!(bitmap.try_insert(65536), Err(Error::OutOfRange(65536)));
+        assert_eq!(bitmap.len(), 4);
+
+        let err = Bitmap::from_iter_checked([1, 2, 70_000, 3]).unwrap_err();
+        insta::assert_snapshot!(err, @"the value 70000 is out of the range of a bitmap (0..=65535)");
+    }
+}
@@ -0,0 +1,191 @@
+use crate::iter::Values;
+use crate::{Bitmap, Word};
+
+/// A read-only view over the complement of a bitmap, see [`Bitmap::complement_view`].
+/// Every word is flipped when it's read, nothing is materialized.
+#[derive(Clone, Copy)]
+pub struct ComplementView<'a> {
+    bitmap: &'a Bitmap,
+}
+
+impl Bitmap {
+    /// Returns a view over all the values *not* present in the bitmap. It can
+    /// be used as the right-hand side of the set operations without flipping
+    /// the 8KiB of the store:
+    /// ```
+    /// use bitmap::Bitmap;
+    ///
+    /// let a = Bitmap::from_iter(0..10);
+    /// let b = Bitmap::from_iter((0..10).step_by(2));
+    /// assert_eq!((a & b.complement_view()).to_vec(), [1, 3, 5, 7, 9]);
+    /// ```
+    #[inline]
+    pub fn complement_view(&self) -> ComplementView<'_> {
+        ComplementView { bitmap: self }
+    }
+
+    /// Keeps the values of `self` absent from `other`, i.e. `self & !other`,
+    /// without materializing `!other`. It runs the vectorized and-not kernels
+    /// (`vbicq_u64` with NEON), [`Bitmap::difference`] is the scalar equivalent.
+    #[inline]
+    pub fn intersect_with_complement(&mut self, other: &Self) {
+        self.difference_simd(other);
+    }
+
+    /// Flips every value of the bitmap: the present values are removed and
+    /// the absent ones are inserted. The length is derived from the previous
+    /// one, the store doesn't need to be counted.
+    #[inline]
+    pub fn complement(&mut self) {
+        self.complement_with(crate::backend::Scalar);
+    }
+
+    /// Same as [`Bitmap::complement`] but vectorized with the kernels selected
+    /// by the features of the crate. There is no bit to count, thus the
+    /// scalar implementation is used when no vectorized kernel is available.
+    #[inline]
+    pub fn complement_simd(&mut self) {
+        #[cfg(any(kernel = "scalar", kernel = "default"))]
+        self.complement_with(crate::backend::Scalar);
+        #[cfg(kernel = "portable")]
+        self.complement_with(crate::portable::Portable);
+        #[cfg(kernel = "neon")]
+        self.complement_with(crate::neon::Neon);
+        #[cfg(kernel = "avx2")]
+        match crate::avx2::Avx2::detect() {
+            Some(avx2) => avx2.complement(self),
+            None => self.complement_with(crate::backend::Scalar),
+        }
+    }
+}
+
+impl<'a> ComplementView<'a> {
+    #[inline]
+    fn words(&self) -> impl Iterator<Item = Word> + 'a {
+        self.bitmap.store.iter().map(|word| !word)
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        !self.bitmap.contains(value)
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        u16::MAX as usize + 1 - self.bitmap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Iterates over the values absent from the original bitmap in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + 'a {
+        Values::new(self.words(), 0)
+    }
+
+    /// Returns the number of values present in `other` and in the view, that is
+    /// the values of `other` absent from the original bitmap.
+    pub fn intersection_len(&self, other: &Bitmap) -> usize {
+        self.words()
+            .zip(other.store.iter())
+            .map(|(l, r)| (l & r).count_ones() as usize)
+            .sum()
+    }
+
+    /// Materialize the complement.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        for (word, complement) in bitmap.store.iter_mut().zip(self.words()) {
+            *word = complement;
+        }
+        bitmap.len = self.len();
+        bitmap.debug_assert_invariants();
+        bitmap
+    }
+}
+
+/// `a & b.complement_view()` computes the difference `a & !b` in place.
+impl std::ops::BitAnd<ComplementView<'_>> for Bitmap {
+    type Output = Bitmap;
+
+    fn bitand(mut self, rhs: ComplementView<'_>) -> Self::Output {
+        self.intersect_with_complement(rhs.bitmap);
+        self
+    }
+}
+
+impl std::ops::Not for Bitmap {
+    type Output = Bitmap;
+
+    fn not(mut self) -> Self::Output {
+        self.complement();
+        self
+    }
+}
+
+impl std::ops::Not for &Bitmap {
+    type Output = Bitmap;
+
+    fn not(self) -> Self::Output {
+        self.complement_view().to_bitmap()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn complement_view() {
+        let bitmap = Bitmap::from_iter(2..=u16::MAX);
+        let view = bitmap.complement_view();
+        assert_eq!(view.len(), 2);
+        assert!(view.contains(1));
+        assert!(!view.contains(2));
+        assert_eq!(view.iter().collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(view.to_bitmap().to_vec(), [0, 1]);
+        assert!(Bitmap::full().complement_view().is_empty());
+
+        let other = Bitmap::from_iter([1, 2, 3]);
+        assert_eq!(view.intersection_len(&other), 1);
+        insta::assert_debug_snapshot!(other.clone() & bitmap.complement_view(), @r###"
+        {
+            1,
+        }
+        "###);
+    }
+
+    #[test]
+    fn complement() {
+        let mut bitmap = Bitmap::from_iter(2..=u16::MAX);
+        bitmap.complement();
+        assert_eq!(bitmap.to_vec(), [0, 1]);
+        assert_eq!(bitmap.len(), 2);
+        assert_eq!(!&bitmap, Bitmap::from_iter(2..=u16::MAX));
+        assert_eq!(!Bitmap::new(), Bitmap::full());
+        assert!((!Bitmap::full()).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_complement_view(left in crate::strategies::any(), right in crate::strategies::any()) {
+            let complement = Bitmap::from_iter((0..=u16::MAX).filter(|v| !right.contains(*v)));
+            let view = right.complement_view();
+            assert_eq!(view.to_bitmap(), complement);
+            assert_eq!(view.len(), complement.len());
+            assert_eq!(view.intersection_len(&left), (left.clone() & &complement).len());
+            assert_eq!(left.clone() & view, left.clone() & &complement);
+            assert_eq!(!&right, complement);
+            let mut filtered = left.clone();
+            filtered.intersect_with_complement(&right);
+            assert_eq!(filtered, left.clone() & &complement);
+            let mut simd = right.clone();
+            simd.complement_simd();
+            assert_eq!(simd, complement);
+            assert_eq!(!right, complement);
+        }
+    }
+}
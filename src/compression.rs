@@ -0,0 +1,101 @@
+use crate::{Bitmap, Error};
+
+/// The algorithm used to compress the raw encoding of a bitmap.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    /// zstd with the given compression level, `0` means zstd's default level.
+    #[cfg(feature = "zstd")]
+    Zstd { level: i32 },
+    #[cfg(feature = "lz4")]
+    Lz4,
+}
+
+impl Compression {
+    // The first byte of a compressed bitmap tells which algorithm was used.
+    #[cfg(feature = "zstd")]
+    const ZSTD_TAG: u8 = 0;
+    #[cfg(feature = "lz4")]
+    const LZ4_TAG: u8 = 1;
+}
+
+impl Bitmap {
+    /// Compress the raw encoding of the bitmap (see [`Bitmap::to_bytes`]).
+    /// The algorithm is recorded in the output, thus [`Bitmap::from_compressed_bytes`]
+    /// doesn't need to be told which one was used.
+    pub fn to_compressed_bytes(&self, compression: Compression) -> Vec<u8> {
+        let raw = self.to_bytes();
+        match compression {
+            #[cfg(feature = "zstd")]
+            Compression::Zstd { level } => {
+                let mut bytes = vec![Compression::ZSTD_TAG];
+                // compressing from a slice into a vec can only fail on OOM
+                zstd::stream::copy_encode(raw.as_slice(), &mut bytes, level).unwrap();
+                bytes
+            }
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => {
+                let mut bytes = vec![Compression::LZ4_TAG];
+                bytes.extend_from_slice(&lz4_flex::compress(&raw));
+                bytes
+            }
+        }
+    }
+
+    /// Decode a bitmap previously encoded with [`Bitmap::to_compressed_bytes`].
+    /// Fails if the bytes are corrupted or were compressed with an algorithm
+    /// that isn't enabled.
+    pub fn from_compressed_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (tag, payload) = bytes
+            .split_first()
+            .ok_or(Error::Corrupted("missing the compression tag"))?;
+        let raw = match *tag {
+            #[cfg(feature = "zstd")]
+            Compression::ZSTD_TAG => zstd::bulk::decompress(payload, Self::RAW_SIZE)
+                .map_err(|_| Error::Corrupted("invalid zstd payload"))?,
+            #[cfg(feature = "lz4")]
+            Compression::LZ4_TAG => lz4_flex::decompress(payload, Self::RAW_SIZE)
+                .map_err(|_| Error::Corrupted("invalid lz4 payload"))?,
+            tag => return Err(Error::UnknownEncoding(tag)),
+        };
+        Self::from_bytes(&raw)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn check_roundtrip(compression: Compression) {
+        let bitmap = Bitmap::from_iter((0..1000).chain(30_000..30_100).chain([u16::MAX]));
+        let bytes = bitmap.to_compressed_bytes(compression);
+        assert!(bytes.len() < Bitmap::RAW_SIZE / 10, "{}", bytes.len());
+        assert_eq!(Bitmap::from_compressed_bytes(&bytes).unwrap(), bitmap);
+
+        assert!(Bitmap::from_compressed_bytes(&bytes[..bytes.len() / 2]).is_err());
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(
+            Bitmap::from_compressed_bytes(&[]),
+            Err(Error::Corrupted("missing the compression tag"))
+        );
+        assert_eq!(
+            Bitmap::from_compressed_bytes(&[42, 0, 0]),
+            Err(Error::UnknownEncoding(42))
+        );
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn zstd_roundtrip() {
+        check_roundtrip(Compression::Zstd { level: 0 });
+        check_roundtrip(Compression::Zstd { level: 19 });
+    }
+
+    #[cfg(feature = "lz4")]
+    #[test]
+    fn lz4_roundtrip() {
+        check_roundtrip(Compression::Lz4);
+    }
+}
@@ -0,0 +1,112 @@
+//! The constant-time variants of the membership queries and updates.
+
+use core::hint::black_box;
+
+use crate::{Bitmap, Word};
+
+/// Returns `Word::MAX` if `left == right` and `0` otherwise, without branching.
+#[inline(always)]
+fn eq_mask(left: usize, right: usize) -> Word {
+    let diff = (left ^ right) as Word;
+    // the high bit of `diff | -diff` is set iff `diff != 0`
+    let not_eq = (diff | diff.wrapping_neg()) >> (Word::BITS - 1);
+    black_box(not_eq).wrapping_sub(1)
+}
+
+impl Bitmap {
+    /// Same as [`Bitmap::contains`] but its timing doesn't depend on the
+    /// values, for the bitmaps holding secrets like the ids of the active
+    /// sessions or tokens. Every constant-time operation reads and writes all
+    /// the words of the store, the word of the value is only selected with
+    /// masks, and the bit math is branchless.
+    ///
+    /// This is a best effort: Rust doesn't guarantee anything about the
+    /// generated code, the masks go through [`black_box`] to keep the optimizer
+    /// from turning them back into branches. The constant-time operations are
+    /// about 1024 times slower than their regular counterpart.
+    #[inline(never)]
+    pub fn contains_ct(&self, value: u16) -> bool {
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
+        let word = self
+            .store
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, word)| acc | (word & eq_mask(i, key)));
+        (word >> bit) & 1 == 1
+    }
+
+    /// Same as [`Bitmap::insert`] in constant time, see [`Bitmap::contains_ct`].
+    /// Returns `true` if the value was not already present in the bitmap.
+    #[inline(never)]
+    pub fn insert_ct(&mut self, value: u16) -> bool {
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
+        let mut old = 0;
+        for (i, word) in self.store.iter_mut().enumerate() {
+            let mask = eq_mask(i, key);
+            old |= *word & mask;
+            *word |= (1 << bit) & mask;
+        }
+        let inserted = !(old >> bit) & 1;
+        self.len += inserted as usize;
+        self.generation.bump();
+        inserted == 1
+    }
+
+    /// Same as [`Bitmap::remove`] in constant time, see [`Bitmap::contains_ct`].
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline(never)]
+    pub fn remove_ct(&mut self, value: u16) -> bool {
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
+        let mut old = 0;
+        for (i, word) in self.store.iter_mut().enumerate() {
+            let mask = eq_mask(i, key);
+            old |= *word & mask;
+            *word &= !((1 << bit) & mask);
+        }
+        let removed = (old >> bit) & 1;
+        self.len -= removed as usize;
+        self.generation.bump();
+        removed == 1
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn constant_time() {
+        assert_eq!(eq_mask(3, 3), Word::MAX);
+        assert_eq!(eq_mask(0, 1023), 0);
+        assert_eq!(eq_mask(1023, 1022), 0);
+
+        let mut bitmap = Bitmap::new();
+        assert!(bitmap.insert_ct(0));
+        assert!(!bitmap.insert_ct(0));
+        assert!(bitmap.insert_ct(u16::MAX));
+        assert!(bitmap.contains_ct(u16::MAX));
+        assert!(!bitmap.contains_ct(1));
+        assert!(bitmap.remove_ct(0));
+        assert!(!bitmap.remove_ct(0));
+        assert_eq!(bitmap.to_vec(), [u16::MAX]);
+        bitmap.assert_invariants();
+    }
+
+    proptest! {
+        #[test]
+        fn prop_constant_time(bitmap in crate::strategies::any(), ops in prop::collection::vec((0..3_u8, any::<u16>()), 0..50)) {
+            let mut expected = bitmap.clone();
+            let mut bitmap = bitmap;
+            for (op, value) in ops {
+                match op {
+                    0 => assert_eq!(bitmap.insert_ct(value), expected.insert(value)),
+                    1 => assert_eq!(bitmap.remove_ct(value), expected.remove(value)),
+                    _ => assert_eq!(bitmap.contains_ct(value), expected.contains(value)),
+                }
+            }
+            bitmap.assert_invariants();
+            assert_eq!(bitmap, expected);
+        }
+    }
+}
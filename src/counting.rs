@@ -0,0 +1,206 @@
+use crate::{Bitmap, Word};
+
+/// A multiset of `u16` where every value is associated with a small 4 bits
+/// saturating counter.
+///
+/// Once a counter reaches [`CountingBitmap::MAX_COUNT`] the real count is
+/// unknown, thus the counter becomes sticky and is never decremented again.
+#[derive(Clone)]
+pub struct CountingBitmap {
+    /// Number of values with a non-zero counter.
+    len: usize,
+    /// Two counters per byte, the even value is in the low nibble.
+    counters: Box<[u8; Self::STORE_SIZE]>,
+}
+
+impl CountingBitmap {
+    const STORE_SIZE: usize = Bitmap::BITMAP_SIZE * Word::BITS as usize / 2;
+
+    /// The value at which a counter saturates.
+    pub const MAX_COUNT: u8 = 0xF;
+
+    pub fn new() -> Self {
+        CountingBitmap {
+            len: 0,
+            counters: vec![0; Self::STORE_SIZE]
+                .into_boxed_slice()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// Number of distinct values with a non-zero count.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    #[inline]
+    fn shift(value: u16) -> u32 {
+        (value as u32 & 1) * 4
+    }
+
+    #[inline]
+    fn set_count(&mut self, value: u16, count: u8) {
+        let shift = Self::shift(value);
+        let byte = &mut self.counters[value as usize / 2];
+        *byte = (*byte & !(0xF << shift)) | (count << shift);
+    }
+
+    /// Returns the count associated with `value`.
+    #[inline]
+    pub fn count(&self, value: u16) -> u8 {
+        (self.counters[value as usize / 2] >> Self::shift(value)) & 0xF
+    }
+
+    /// Increment the counter of `value` and returns its new count.
+    #[inline]
+    pub fn add(&mut self, value: u16) -> u8 {
+        let count = self.count(value);
+        if count == 0 {
+            self.len += 1;
+        }
+        let count = (count + 1).min(Self::MAX_COUNT);
+        self.set_count(value, count);
+        count
+    }
+
+    /// Decrement the counter of `value` and returns its new count.
+    /// Saturated counters are left untouched.
+    #[inline]
+    pub fn remove(&mut self, value: u16) -> u8 {
+        let count = self.count(value);
+        if count == 0 || count == Self::MAX_COUNT {
+            return count;
+        }
+        if count == 1 {
+            self.len -= 1;
+        }
+        self.set_count(value, count - 1);
+        count - 1
+    }
+
+    /// Returns the set of values whose count is at least `threshold`.
+    /// A threshold of `0` returns a full bitmap.
+    pub fn to_bitmap(&self, threshold: u8) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        let mut count = 0;
+        // every word of the bitmap is made of 64 counters stored on 32 bytes
+        let bytes_per_word = Word::BITS as usize / 2;
+        for (word, bytes) in bitmap
+            .store
+            .iter_mut()
+            .zip(self.counters.chunks_exact(bytes_per_word))
+        {
+            for (i, byte) in bytes.iter().enumerate() {
+                let (low, high) = (byte & 0xF, byte >> 4);
+                *word |= ((low >= threshold) as Word) << (2 * i);
+                *word |= ((high >= threshold) as Word) << (2 * i + 1);
+            }
+            count += word.count_ones();
+        }
+        bitmap.len = count as usize;
+        bitmap.debug_assert_invariants();
+        bitmap
+    }
+}
+
+impl Default for CountingBitmap {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl FromIterator<u16> for CountingBitmap {
+    fn from_iter<T: IntoIterator<Item = u16>>(iter: T) -> Self {
+        let mut bitmap = CountingBitmap::new();
+        iter.into_iter().for_each(|value| {
+            bitmap.add(value);
+        });
+        bitmap
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn add_remove() {
+        let mut bitmap = CountingBitmap::new();
+        assert_eq!(bitmap.add(3), 1);
+        assert_eq!(bitmap.add(3), 2);
+        assert_eq!(bitmap.add(4), 1);
+        assert_eq!(bitmap.add(u16::MAX), 1);
+        assert_eq!(bitmap.len(), 3);
+        assert_eq!(bitmap.count(2), 0);
+        assert_eq!(bitmap.count(3), 2);
+        assert_eq!(bitmap.count(4), 1);
+
+        assert_eq!(bitmap.remove(3), 1);
+        assert_eq!(bitmap.remove(4), 0);
+        assert_eq!(bitmap.remove(4), 0);
+        assert_eq!(bitmap.len(), 2);
+
+        insta::assert_debug_snapshot!(bitmap.to_bitmap(1), @r###"
+        {
+            3,
+            65535,
+        }
+        "###);
+    }
+
+    #[test]
+    fn saturate() {
+        let mut bitmap = CountingBitmap::new();
+        for _ in 0..100 {
+            bitmap.add(12);
+        }
+        assert_eq!(bitmap.count(12), CountingBitmap::MAX_COUNT);
+        assert_eq!(bitmap.remove(12), CountingBitmap::MAX_COUNT);
+        // the neighbour sharing the same byte must not be impacted
+        assert_eq!(bitmap.count(13), 0);
+        assert_eq!(bitmap.len(), 1);
+    }
+
+    #[test]
+    fn threshold() {
+        let bitmap = CountingBitmap::from_iter([1, 1, 1, 2, 2, 3, 64, 64, 65]);
+        insta::assert_debug_snapshot!(bitmap.to_bitmap(2), @r###"
+        {
+            1,
+            2,
+            64,
+        }
+        "###);
+        insta::assert_debug_snapshot!(bitmap.to_bitmap(3), @r###"
+        {
+            1,
+        }
+        "###);
+        assert_eq!(bitmap.to_bitmap(0), Bitmap::full());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_to_bitmap(values in prop::collection::vec(0..=u16::MAX, 1..150), threshold in 1..4_u8) {
+            let counting = CountingBitmap::from_iter(values.iter().copied());
+            let mut expected: Vec<u16> = values
+                .iter()
+                .copied()
+                .filter(|v| values.iter().filter(|x| *x == v).count() >= threshold as usize)
+                .collect();
+            expected.sort_unstable();
+            expected.dedup();
+
+            assert_eq!(counting.to_bitmap(threshold).to_vec(), expected);
+        }
+    }
+}
@@ -0,0 +1,96 @@
+use core::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use crate::Bitmap;
+
+impl Bitmap {
+    /// Returns the positions of the `candidates` picked by the greedy set cover
+    /// algorithm to cover `universe`, in the order they were picked: the
+    /// candidate covering the most uncovered values is picked until all the
+    /// values are covered. The values absent from every candidate are ignored.
+    ///
+    /// The cover is at most `ln(universe.len()) + 1` times larger than the
+    /// smallest one. Since the number of uncovered values of a candidate can
+    /// only decrease, it's only recomputed when the candidate reaches the top
+    /// of the queue.
+    pub fn set_cover(universe: &Bitmap, candidates: &[Bitmap]) -> Vec<usize> {
+        let mut uncovered = universe.clone();
+        let gain = |candidate: &Bitmap, uncovered: &Bitmap| {
+            candidate.fold_words_with(uncovered, 0, |count, left, right| {
+                count + (left & right).count_ones() as usize
+            })
+        };
+        // the ties are broken in favor of the first candidates
+        let mut queue: BinaryHeap<(usize, Reverse<usize>)> = candidates
+            .iter()
+            .enumerate()
+            .map(|(i, candidate)| (gain(candidate, &uncovered), Reverse(i)))
+            .filter(|(gain, _)| *gain != 0)
+            .collect();
+
+        let mut cover = Vec::new();
+        while let Some((stale, Reverse(i))) = queue.pop() {
+            let fresh = gain(&candidates[i], &uncovered);
+            if fresh == 0 {
+                continue;
+            }
+            if queue.peek().is_some_and(|best| (fresh, Reverse(i)) < *best) {
+                debug_assert!(fresh < stale);
+                queue.push((fresh, Reverse(i)));
+                continue;
+            }
+            for (word, covered) in uncovered.store.iter_mut().zip(&candidates[i].store) {
+                *word &= !covered;
+            }
+            uncovered.len -= fresh;
+            uncovered.debug_assert_invariants();
+            cover.push(i);
+        }
+        cover
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn set_cover() {
+        let universe = Bitmap::from_iter(0..10);
+        let candidates = [
+            Bitmap::from_iter(0..5),
+            Bitmap::from_iter(4..8),
+            Bitmap::from_iter([8, 9, 100]),
+            Bitmap::from_iter(0..8),
+            Bitmap::new(),
+        ];
+        assert_eq!(Bitmap::set_cover(&universe, &candidates), [3, 2]);
+        assert!(Bitmap::set_cover(&universe, &[]).is_empty());
+        assert!(Bitmap::set_cover(&Bitmap::new(), &candidates).is_empty());
+        // the values that can't be covered are ignored
+        assert_eq!(
+            Bitmap::set_cover(&Bitmap::from_iter([1, 50]), &candidates),
+            [0]
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_set_cover(universe in crate::strategies::any(), candidates in prop::collection::vec(crate::strategies::any(), 0..8)) {
+            let cover = Bitmap::set_cover(&universe, &candidates);
+            let covered = cover.iter().fold(Bitmap::new(), |covered, i| covered | &candidates[*i]);
+            let coverable = candidates.iter().fold(Bitmap::new(), |covered, candidate| covered | candidate);
+            assert_eq!(covered & &universe, coverable & &universe);
+
+            // every pick covers new values, as much as the best remaining candidate
+            let mut uncovered = universe;
+            for i in cover {
+                let gains: Vec<usize> = candidates.iter().map(|c| (c.clone() & &uncovered).len()).collect();
+                assert_ne!(gains[i], 0);
+                assert_eq!(gains[i], *gains.iter().max().unwrap());
+                uncovered = Bitmap::from_iter(uncovered.difference_iter(&candidates[i]));
+            }
+        }
+    }
+}
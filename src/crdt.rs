@@ -0,0 +1,249 @@
+//! Conflict-free replicated sets of `u16` built on bitmaps.
+//!
+//! Both sets are state-based CRDTs: the replicas converge by merging their
+//! states, the merge being a union of bitmaps. To avoid shipping the whole
+//! state on every synchronization, they also keep track of the updates made
+//! since the last call to `take_delta`. The delta is itself a set that can
+//! be merged in any other replica.
+
+use crate::{Bitmap, BitmapOps, Error};
+
+/// A grow-only set: values can be inserted but never removed.
+#[derive(Clone, Default)]
+pub struct GSet {
+    values: Bitmap,
+    delta: Bitmap,
+}
+
+impl GSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the value was not already present in the set.
+    pub fn insert(&mut self, value: u16) -> bool {
+        self.delta.insert(value);
+        self.values.insert(value)
+    }
+
+    pub fn contains(&self, value: u16) -> bool {
+        self.values.contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    pub fn values(&self) -> &Bitmap {
+        &self.values
+    }
+
+    /// Merge the state (or a delta) of another replica.
+    pub fn merge(&mut self, other: &GSet) {
+        self.values.union_with(&other.values);
+    }
+
+    /// Returns the values inserted since the last call as a set to merge
+    /// in the other replicas.
+    pub fn take_delta(&mut self) -> GSet {
+        GSet {
+            values: std::mem::take(&mut self.delta),
+            delta: Bitmap::new(),
+        }
+    }
+
+    /// Encode the state of the set with the raw encoding of the bitmaps.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.values.to_bytes()
+    }
+
+    /// Decode a set encoded with [`GSet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        Ok(GSet {
+            values: Bitmap::from_bytes(bytes)?,
+            delta: Bitmap::new(),
+        })
+    }
+}
+
+/// Two replicas are equal if they hold the same values, whatever their pending delta.
+impl PartialEq for GSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+/// A two-phase set: values can be inserted and removed, but once a value
+/// has been removed it can never be inserted again.
+#[derive(Clone, Default, PartialEq)]
+pub struct TwoPSet {
+    added: GSet,
+    removed: GSet,
+}
+
+impl TwoPSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `true` if the value was inserted, i.e. it was neither present
+    /// in the set nor removed before.
+    pub fn insert(&mut self, value: u16) -> bool {
+        !self.removed.contains(value) && self.added.insert(value)
+    }
+
+    /// Returns `true` if the value was present in the set.
+    pub fn remove(&mut self, value: u16) -> bool {
+        self.contains(value) && self.removed.insert(value)
+    }
+
+    pub fn contains(&self, value: u16) -> bool {
+        self.added.contains(value) && !self.removed.contains(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.added
+            .values
+            .difference_iter(&self.removed.values)
+            .count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the values currently present in the set.
+    pub fn values(&self) -> Bitmap {
+        Bitmap::from_iter(self.added.values.difference_iter(&self.removed.values))
+    }
+
+    /// Merge the state (or a delta) of another replica.
+    pub fn merge(&mut self, other: &TwoPSet) {
+        self.added.merge(&other.added);
+        self.removed.merge(&other.removed);
+    }
+
+    /// Returns the insertions and removals made since the last call as a set
+    /// to merge in the other replicas.
+    pub fn take_delta(&mut self) -> TwoPSet {
+        TwoPSet {
+            added: self.added.take_delta(),
+            removed: self.removed.take_delta(),
+        }
+    }
+
+    /// Encode the state of the set as the raw encoding of the inserted values
+    /// followed by the raw encoding of the removed values.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = self.added.to_bytes();
+        bytes.extend_from_slice(&self.removed.to_bytes());
+        bytes
+    }
+
+    /// Decode a set encoded with [`TwoPSet::to_bytes`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != 2 * Bitmap::RAW_SIZE {
+            return Err(Error::InvalidLength {
+                expected: 2 * Bitmap::RAW_SIZE,
+                found: bytes.len(),
+            });
+        }
+        let (added, removed) = bytes.split_at(Bitmap::RAW_SIZE);
+        Ok(TwoPSet {
+            added: GSet::from_bytes(added)?,
+            removed: GSet::from_bytes(removed)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn gset() {
+        let mut left = GSet::new();
+        let mut right = GSet::new();
+        left.insert(1);
+        left.insert(2);
+        right.insert(2);
+        right.insert(u16::MAX);
+
+        let delta = GSet::from_bytes(&left.take_delta().to_bytes()).unwrap();
+        right.merge(&delta);
+        left.merge(&right.take_delta());
+        assert!(left == right);
+        insta::assert_debug_snapshot!(left.values(), @r###"
+        {
+            1,
+            2,
+            65535,
+        }
+        "###);
+        assert!(left.take_delta().is_empty());
+    }
+
+    #[test]
+    fn two_phase_set() {
+        let mut left = TwoPSet::new();
+        let mut right = TwoPSet::new();
+        assert!(left.insert(1));
+        assert!(left.insert(2));
+        assert!(left.remove(1));
+        assert!(!left.remove(1));
+        assert!(!left.insert(1));
+        right.insert(1);
+        right.insert(3);
+
+        let delta = TwoPSet::from_bytes(&left.take_delta().to_bytes()).unwrap();
+        right.merge(&delta);
+        left.merge(&right.take_delta());
+        assert!(left == right);
+        assert_eq!(left.len(), 2);
+        insta::assert_debug_snapshot!(left.values(), @r###"
+        {
+            2,
+            3,
+        }
+        "###);
+        assert_eq!(
+            TwoPSet::from_bytes(&[0; Bitmap::RAW_SIZE]).err(),
+            Some(Error::InvalidLength {
+                expected: 2 * Bitmap::RAW_SIZE,
+                found: Bitmap::RAW_SIZE
+            })
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_convergence(ops in prop::collection::vec((0..3_usize, any::<bool>(), 0..100_u16), 0..100)) {
+            // three replicas receiving the deltas of the others in different orders
+            let mut replicas = [TwoPSet::new(), TwoPSet::new(), TwoPSet::new()];
+            let mut deltas = Vec::new();
+            for (replica, insert, value) in ops {
+                if insert {
+                    replicas[replica].insert(value);
+                } else {
+                    replicas[replica].remove(value);
+                }
+                deltas.push((replica, replicas[replica].take_delta()));
+            }
+            for (i, replica) in replicas.iter_mut().enumerate() {
+                let others = deltas.iter().filter(|(from, _)| *from != i);
+                if i % 2 == 0 {
+                    others.for_each(|(_, delta)| replica.merge(delta));
+                } else {
+                    others.rev().for_each(|(_, delta)| replica.merge(delta));
+                }
+            }
+            assert!(replicas[0] == replicas[1]);
+            assert!(replicas[1] == replicas[2]);
+        }
+    }
+}
@@ -0,0 +1,67 @@
+//! Conversions from and to the [`croaring::Bitmap`] of the CRoaring bindings.
+//!
+//! A [`croaring::BitmapView`], for example over a buffer in the frozen format,
+//! dereferences to a [`croaring::Bitmap`] and can thus be converted the same way.
+
+use crate::{Bitmap, Error};
+
+/// The runs of consecutive values are inserted all at once.
+impl From<&Bitmap> for croaring::Bitmap {
+    fn from(bitmap: &Bitmap) -> Self {
+        let mut croaring = croaring::Bitmap::new();
+        for range in bitmap.ranges() {
+            croaring.add_range(*range.start() as u32..=*range.end() as u32);
+        }
+        croaring
+    }
+}
+
+/// Fails if the CRoaring bitmap contains a value that doesn't fit in a `u16`.
+impl TryFrom<&croaring::Bitmap> for Bitmap {
+    type Error = Error;
+
+    fn try_from(croaring: &croaring::Bitmap) -> Result<Self, Error> {
+        match croaring.maximum() {
+            Some(max) if max > u16::MAX as u32 => return Err(Error::OutOfRange(max)),
+            _ => (),
+        }
+        let mut bitmap = Bitmap::new();
+        for value in croaring.iter() {
+            bitmap.insert_unchecked(value as u16);
+        }
+        bitmap.recompute_len();
+        Ok(bitmap)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn croaring() {
+        let bitmap = Bitmap::from_iter((0..100).chain([1000, u16::MAX]));
+        let croaring = croaring::Bitmap::from(&bitmap);
+        assert_eq!(croaring.cardinality(), 102);
+        assert_eq!(Bitmap::try_from(&croaring), Ok(bitmap.clone()));
+
+        let mut buffer = Vec::new();
+        let frozen = croaring.serialize_into_vec::<croaring::Frozen>(&mut buffer);
+        // safety: the buffer was just serialized in the frozen format
+        let view = unsafe { croaring::BitmapView::deserialize::<croaring::Frozen>(frozen) };
+        assert_eq!(Bitmap::try_from(&*view), Ok(bitmap));
+
+        let wide = croaring::Bitmap::of(&[1, 70_000, 80_000]);
+        assert_eq!(Bitmap::try_from(&wide), Err(Error::OutOfRange(80_000)));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_roundtrip(bitmap in crate::strategies::any()) {
+            let croaring = croaring::Bitmap::from(&bitmap);
+            assert_eq!(croaring.cardinality(), bitmap.len() as u64);
+            assert_eq!(Bitmap::try_from(&croaring).unwrap(), bitmap);
+        }
+    }
+}
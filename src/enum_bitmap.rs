@@ -0,0 +1,201 @@
+use core::fmt;
+use core::marker::PhantomData;
+
+use crate::Bitmap;
+
+/// A set of the variants of `E` backed by a [`Bitmap`], every variant being
+/// stored at the position given by its `Into<u16>` conversion.
+///
+/// Unlike the sets stored in a single integer it scales to enums of up to
+/// 65536 variants, and the operations between two sets use the kernels of
+/// [`Bitmap`].
+/// ```
+/// use bitmap::EnumBitmap;
+///
+/// #[derive(Debug, Clone, Copy, PartialEq)]
+/// enum Color {
+///     Red,
+///     Green,
+///     Blue,
+/// }
+///
+/// impl From<Color> for u16 {
+///     fn from(color: Color) -> u16 {
+///         color as u16
+///     }
+/// }
+///
+/// impl TryFrom<u16> for Color {
+///     type Error = u16;
+///
+///     fn try_from(value: u16) -> Result<Self, u16> {
+///         [Color::Red, Color::Green, Color::Blue].get(value as usize).copied().ok_or(value)
+///     }
+/// }
+///
+/// let colors = EnumBitmap::from_iter([Color::Blue, Color::Red]);
+/// assert!(colors.contains(Color::Red));
+/// assert!(!colors.contains(Color::Green));
+/// assert_eq!(colors.iter().collect::<Vec<_>>(), [Color::Red, Color::Blue]);
+/// ```
+pub struct EnumBitmap<E> {
+    bitmap: Bitmap,
+    _marker: PhantomData<fn() -> E>,
+}
+
+impl<E: Into<u16> + TryFrom<u16>> EnumBitmap<E> {
+    pub const fn new() -> Self {
+        EnumBitmap {
+            bitmap: Bitmap::new(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Wraps a bitmap, dropping its values that are not a variant of `E`.
+    pub fn from_bitmap(mut bitmap: Bitmap) -> Self {
+        bitmap.retain(|value| E::try_from(value).is_ok());
+        EnumBitmap {
+            bitmap,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the underlying bitmap.
+    #[inline]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    pub fn into_bitmap(self) -> Bitmap {
+        self.bitmap
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    /// Returns `true` if the variant was not already present in the set.
+    #[inline]
+    pub fn insert(&mut self, variant: E) -> bool {
+        self.bitmap.insert(variant.into())
+    }
+
+    /// Returns `true` if the variant was present in the set.
+    #[inline]
+    pub fn remove(&mut self, variant: E) -> bool {
+        self.bitmap.remove(variant.into())
+    }
+
+    #[inline]
+    pub fn contains(&self, variant: E) -> bool {
+        self.bitmap.contains(variant.into())
+    }
+
+    /// Keeps only the variants also present in `other`.
+    #[inline]
+    pub fn intersection(&mut self, other: &Self) {
+        self.bitmap.intersection(&other.bitmap);
+    }
+
+    /// Iterates over the variants of the set in the order of their `u16`.
+    pub fn iter(&self) -> impl Iterator<Item = E> + '_ {
+        self.bitmap
+            .iter()
+            .filter_map(|value| E::try_from(value).ok())
+    }
+}
+
+impl<E: Into<u16> + TryFrom<u16>> FromIterator<E> for EnumBitmap<E> {
+    fn from_iter<T: IntoIterator<Item = E>>(iter: T) -> Self {
+        Self::from_bitmap(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<E: Into<u16> + TryFrom<u16>> Default for EnumBitmap<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E> Clone for EnumBitmap<E> {
+    fn clone(&self) -> Self {
+        EnumBitmap {
+            bitmap: self.bitmap.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<E> PartialEq for EnumBitmap<E> {
+    fn eq(&self, other: &Self) -> bool {
+        self.bitmap == other.bitmap
+    }
+}
+
+impl<E> Eq for EnumBitmap<E> {}
+
+impl<E: Into<u16> + TryFrom<u16> + fmt::Debug> fmt::Debug for EnumBitmap<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// An enum with more variants than the bits of a word.
+    #[derive(Debug, Clone, Copy, PartialEq)]
+    struct Variant(u16);
+
+    impl From<Variant> for u16 {
+        fn from(variant: Variant) -> u16 {
+            variant.0
+        }
+    }
+
+    impl TryFrom<u16> for Variant {
+        type Error = ();
+
+        fn try_from(value: u16) -> Result<Self, ()> {
+            (value < 1000).then_some(Variant(value)).ok_or(())
+        }
+    }
+
+    #[test]
+    fn enum_bitmap() {
+        let mut set = EnumBitmap::from_iter([Variant(3), Variant(999), Variant(3)]);
+        assert_eq!(set.len(), 2);
+        assert!(set.insert(Variant(64)));
+        assert!(!set.insert(Variant(64)));
+        assert!(set.contains(Variant(999)));
+        assert!(set.remove(Variant(999)));
+        assert!(!set.contains(Variant(999)));
+        insta::assert_debug_snapshot!(set, @r###"
+        {
+            Variant(
+                3,
+            ),
+            Variant(
+                64,
+            ),
+        }
+        "###);
+
+        set.intersection(&EnumBitmap::from_iter([Variant(64), Variant(500)]));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [Variant(64)]);
+
+        // the values that are not variants are dropped
+        let set = EnumBitmap::<Variant>::from_bitmap(Bitmap::from_iter([10, 1000, u16::MAX]));
+        assert_eq!(set.iter().collect::<Vec<_>>(), [Variant(10)]);
+        assert_eq!(set.len(), 1);
+        assert_eq!(set.bitmap().to_vec(), [10]);
+    }
+}
@@ -0,0 +1,65 @@
+use core::fmt;
+
+/// The error returned by the fallible operations of the crate, like the
+/// decoding of the bitmaps or the checked conversions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "uniffi", derive(uniffi::Error), uniffi(flat_error))]
+#[non_exhaustive]
+pub enum Error {
+    /// A buffer doesn't have the length required by its encoding.
+    InvalidLength { expected: usize, found: usize },
+    /// A buffer doesn't end with the magic of the [`BitmapFile`](crate::BitmapFile) format.
+    InvalidMagic,
+    /// A value doesn't fit in the `u16` universe of a bitmap.
+    OutOfRange(u32),
+    /// An entry of a [`BitmapFile`](crate::BitmapFile) isn't aligned as
+    /// required by its encoding.
+    Misaligned { offset: u64 },
+    /// A buffer was encoded with an unknown encoding or a compression
+    /// algorithm that isn't enabled.
+    UnknownEncoding(u8),
+    /// A buffer is inconsistent, the reason is given.
+    Corrupted(&'static str),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::InvalidLength { expected, found } => {
+                write!(
+                    f,
+                    "expected a buffer of {expected} bytes, got {found} bytes"
+                )
+            }
+            Error::InvalidMagic => {
+                write!(f, "the buffer doesn't end with the magic of a bitmap file")
+            }
+            Error::OutOfRange(value) => write!(
+                f,
+                "the value {value} is out of the range of a bitmap (0..=65535)"
+            ),
+            Error::Misaligned { offset } => {
+                write!(f, "the entry at offset {offset} isn't properly aligned")
+            }
+            Error::UnknownEncoding(tag) => write!(f, "unknown encoding {tag}"),
+            Error::Corrupted(reason) => write!(f, "corrupted buffer: {reason}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn display() {
+        insta::assert_snapshot!(Error::InvalidLength { expected: 8192, found: 3 }, @"expected a buffer of 8192 bytes, got 3 bytes");
+        insta::assert_snapshot!(Error::InvalidMagic, @"the buffer doesn't end with the magic of a bitmap file");
+        insta::assert_snapshot!(Error::OutOfRange(70_000), @"the value 70000 is out of the range of a bitmap (0..=65535)");
+        insta::assert_snapshot!(Error::Misaligned { offset: 13 }, @"the entry at offset 13 isn't properly aligned");
+        insta::assert_snapshot!(Error::UnknownEncoding(42), @"unknown encoding 42");
+        insta::assert_snapshot!(Error::Corrupted("the index is out of bounds"), @"corrupted buffer: the index is out of bounds");
+    }
+}
@@ -0,0 +1,123 @@
+//! The UniFFI bindings, so the Swift and Kotlin apps can evaluate the same
+//! serialized bitmaps as the backend. The bindings are generated from the
+//! library built with `cargo rustc --release --features uniffi --crate-type cdylib`
+//! by `uniffi-bindgen generate --library`.
+//!
+//! The objects are shared between threads by the foreign languages, thus the
+//! bitmap lives behind a mutex.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::{Bitmap, Error};
+
+/// A bitmap exposed to the foreign languages.
+#[derive(uniffi::Object)]
+pub struct FfiBitmap {
+    bitmap: Mutex<Bitmap>,
+}
+
+impl FfiBitmap {
+    fn wrap(bitmap: Bitmap) -> Arc<Self> {
+        Arc::new(FfiBitmap {
+            bitmap: Mutex::new(bitmap),
+        })
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Bitmap> {
+        // a panic can't leave a bitmap in an invalid state
+        self.bitmap
+            .lock()
+            .unwrap_or_else(|poison| poison.into_inner())
+    }
+
+    /// Copies the bitmap, so `self` and `other` are never locked together
+    /// even when they are the same object.
+    fn snapshot(&self) -> Bitmap {
+        self.lock().clone()
+    }
+}
+
+#[uniffi::export]
+impl FfiBitmap {
+    #[uniffi::constructor]
+    pub fn new() -> Arc<Self> {
+        Self::wrap(Bitmap::new())
+    }
+
+    /// Decodes a bitmap encoded with [`Bitmap::to_bytes`].
+    #[uniffi::constructor]
+    pub fn from_bytes(bytes: Vec<u8>) -> Result<Arc<Self>, Error> {
+        Bitmap::from_bytes(&bytes).map(Self::wrap)
+    }
+
+    /// Returns `true` if the value was not already present in the bitmap.
+    pub fn insert(&self, value: u16) -> bool {
+        self.lock().insert(value)
+    }
+
+    /// Returns `true` if the value was present in the bitmap.
+    pub fn remove(&self, value: u16) -> bool {
+        self.lock().remove(value)
+    }
+
+    pub fn contains(&self, value: u16) -> bool {
+        self.lock().contains(value)
+    }
+
+    pub fn len(&self) -> u32 {
+        self.lock().len() as u32
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns a new bitmap holding the values present in both bitmaps.
+    pub fn and(&self, other: Arc<FfiBitmap>) -> Arc<FfiBitmap> {
+        Self::wrap(self.snapshot() & &other.snapshot())
+    }
+
+    /// Returns a new bitmap holding the values present in any bitmap.
+    pub fn or(&self, other: Arc<FfiBitmap>) -> Arc<FfiBitmap> {
+        Self::wrap(self.snapshot() | &other.snapshot())
+    }
+
+    /// Encodes the bitmap with [`Bitmap::to_bytes`].
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.lock().to_bytes()
+    }
+
+    /// Returns the values in increasing order.
+    pub fn values(&self) -> Vec<u16> {
+        self.lock().to_vec()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn ffi() {
+        let left = FfiBitmap::new();
+        assert!(left.insert(3));
+        assert!(left.insert(u16::MAX));
+        assert!(!left.insert(3));
+        assert!(left.contains(3));
+        assert_eq!(left.len(), 2);
+
+        let right = FfiBitmap::from_bytes(Bitmap::from_iter([3, 4]).to_bytes()).unwrap();
+        assert_eq!(left.and(right.clone()).values(), [3]);
+        assert_eq!(left.or(right).values(), [3, 4, u16::MAX]);
+        // the same object on both sides doesn't deadlock
+        assert_eq!(left.and(left.clone()).values(), [3, u16::MAX]);
+
+        let decoded = FfiBitmap::from_bytes(left.to_bytes()).unwrap();
+        assert!(decoded.remove(3));
+        assert_eq!(decoded.values(), [u16::MAX]);
+        assert!(matches!(
+            FfiBitmap::from_bytes(vec![0; 3]),
+            Err(Error::InvalidLength { found: 3, .. })
+        ));
+    }
+}
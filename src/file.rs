@@ -0,0 +1,405 @@
+//! A file format packing many bitmaps together.
+//!
+//! ```text
+//! entries   the encoded bitmaps one after the other, the raw entries are
+//!           aligned on 8 bytes
+//! index     16 bytes per entry: offset (u64), length (u32), encoding (u8) and
+//!           3 bytes of padding
+//! footer    offset of the index (u64), number of entries (u32) and the magic `BMPF`
+//! ```
+//!
+//! All the integers are stored in little endian. An entry is either encoded
+//! with the raw encoding of [`Bitmap::to_bytes`], or as a list of runs of
+//! consecutive values, each run being its first and last value stored as `u16`.
+//! The writer picks the smallest encoding for every bitmap.
+//!
+//! The reader works on a borrowed slice of bytes, typically a memory mapped file,
+//! and doesn't copy anything: the raw entries are returned as [`BitmapView`]s.
+
+use std::io::{self, Write};
+
+use crate::{Bitmap, BitmapView, Error};
+
+const MAGIC: &[u8; 4] = b"BMPF";
+const INDEX_ENTRY_SIZE: usize = 16;
+const FOOTER_SIZE: usize = 16;
+const RAW_ALIGNMENT: u64 = 8;
+const RUN_SIZE: usize = 4;
+
+const RAW_ENCODING: u8 = 0;
+const RUNS_ENCODING: u8 = 1;
+
+/// Writes many bitmaps in a single file, see the [module documentation](self) for the format.
+pub struct BitmapFileWriter<W: Write> {
+    writer: W,
+    offset: u64,
+    index: Vec<u8>,
+    entries: u32,
+}
+
+impl<W: Write> BitmapFileWriter<W> {
+    pub fn new(writer: W) -> Self {
+        BitmapFileWriter {
+            writer,
+            offset: 0,
+            index: Vec::new(),
+            entries: 0,
+        }
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> io::Result<()> {
+        self.writer.write_all(bytes)?;
+        self.offset += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Append a bitmap to the file and returns its position in the file.
+    pub fn push(&mut self, bitmap: &Bitmap) -> io::Result<usize> {
+        let runs = bitmap.to_ranges();
+        let (encoding, len) = if runs.len() * RUN_SIZE < Bitmap::RAW_SIZE {
+            (RUNS_ENCODING, runs.len() * RUN_SIZE)
+        } else {
+            (RAW_ENCODING, Bitmap::RAW_SIZE)
+        };
+
+        if encoding == RAW_ENCODING {
+            let padding = self.offset.next_multiple_of(RAW_ALIGNMENT) - self.offset;
+            self.write(&[0; RAW_ALIGNMENT as usize][..padding as usize])?;
+        }
+        self.index.extend_from_slice(&self.offset.to_le_bytes());
+        self.index.extend_from_slice(&(len as u32).to_le_bytes());
+        self.index.extend_from_slice(&[encoding, 0, 0, 0]);
+
+        match encoding {
+            RAW_ENCODING => {
+                bitmap.write_to(&mut self.writer)?;
+                self.offset += Bitmap::RAW_SIZE as u64;
+            }
+            _ => {
+                for run in runs {
+                    self.write(&run.start().to_le_bytes())?;
+                    self.write(&run.end().to_le_bytes())?;
+                }
+            }
+        }
+
+        self.entries += 1;
+        Ok(self.entries as usize - 1)
+    }
+
+    /// Write the index and the footer, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        let index_offset = self.offset;
+        let index = std::mem::take(&mut self.index);
+        self.write(&index)?;
+        self.write(&index_offset.to_le_bytes())?;
+        self.write(&self.entries.to_le_bytes())?;
+        self.write(MAGIC)?;
+        self.writer.flush()?;
+        Ok(self.writer)
+    }
+}
+
+/// Reads a file written by a [`BitmapFileWriter`].
+#[derive(Clone, Copy)]
+pub struct BitmapFile<'a> {
+    bytes: &'a [u8],
+    index: &'a [u8],
+}
+
+impl<'a> BitmapFile<'a> {
+    /// Fails if the footer or the index are corrupted.
+    /// The entries themselves are only checked when they're accessed.
+    pub fn open(bytes: &'a [u8]) -> Result<Self, Error> {
+        let footer_offset = bytes
+            .len()
+            .checked_sub(FOOTER_SIZE)
+            .ok_or(Error::InvalidLength {
+                expected: FOOTER_SIZE,
+                found: bytes.len(),
+            })?;
+        let footer = &bytes[footer_offset..];
+        if &footer[12..] != MAGIC {
+            return Err(Error::InvalidMagic);
+        }
+        let index_offset = u64::from_le_bytes(footer[..8].try_into().unwrap());
+        let entries = u32::from_le_bytes(footer[8..12].try_into().unwrap());
+        let index_len = (entries as usize)
+            .checked_mul(INDEX_ENTRY_SIZE)
+            .ok_or(Error::Corrupted("the index is too large"))?;
+        let index_offset = usize::try_from(index_offset)
+            .ok()
+            .filter(|offset| offset.checked_add(index_len) == Some(footer_offset))
+            .ok_or(Error::Corrupted("the index doesn't end at the footer"))?;
+        Ok(BitmapFile {
+            bytes,
+            index: &bytes[index_offset..][..index_len],
+        })
+    }
+
+    /// Number of bitmaps in the file.
+    pub fn len(&self) -> usize {
+        self.index.len() / INDEX_ENTRY_SIZE
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the bitmap at position `i`, `None` if it's out of bounds, or the
+    /// error describing why the entry is corrupted.
+    pub fn get(&self, i: usize) -> Option<Result<Entry<'a>, Error>> {
+        let start = i.checked_mul(INDEX_ENTRY_SIZE)?;
+        let entry = self
+            .index
+            .get(start..start.checked_add(INDEX_ENTRY_SIZE)?)?;
+        Some(self.decode(entry))
+    }
+
+    fn decode(&self, entry: &[u8]) -> Result<Entry<'a>, Error> {
+        let offset = u64::from_le_bytes(entry[..8].try_into().unwrap());
+        let len = u32::from_le_bytes(entry[8..12].try_into().unwrap());
+        let payload = usize::try_from(offset)
+            .ok()
+            .and_then(|offset| self.bytes.get(offset..)?.get(..len as usize))
+            .ok_or(Error::Corrupted(
+                "the entry is out of the bounds of the file",
+            ))?;
+        match entry[12] {
+            RAW_ENCODING if offset % RAW_ALIGNMENT != 0 => Err(Error::Misaligned { offset }),
+            RAW_ENCODING => BitmapView::new(payload).map(Entry::Raw),
+            RUNS_ENCODING if payload.len() % RUN_SIZE != 0 => Err(Error::InvalidLength {
+                expected: payload.len().next_multiple_of(RUN_SIZE),
+                found: payload.len(),
+            }),
+            RUNS_ENCODING => {
+                let runs = Runs(payload);
+                // the binary search and the length rely on sorted and disjoint runs
+                let mut previous_end = None;
+                for (start, end) in runs.iter() {
+                    if start > end {
+                        return Err(Error::Corrupted("a run ends before it starts"));
+                    }
+                    if previous_end.is_some_and(|previous| start <= previous) {
+                        return Err(Error::Corrupted("the runs are unsorted or overlap"));
+                    }
+                    previous_end = Some(end);
+                }
+                Ok(Entry::Runs(runs))
+            }
+            encoding => Err(Error::UnknownEncoding(encoding)),
+        }
+    }
+
+    /// Iterates over all the bitmaps of the file, the corrupted entries are
+    /// returned as errors.
+    pub fn iter(&self) -> impl Iterator<Item = Result<Entry<'a>, Error>> + '_ {
+        (0..self.len()).filter_map(|i| self.get(i))
+    }
+}
+
+/// A bitmap read from a [`BitmapFile`].
+#[derive(Clone, Copy)]
+pub enum Entry<'a> {
+    Raw(BitmapView<'a>),
+    Runs(Runs<'a>),
+}
+
+impl Entry<'_> {
+    pub fn contains(&self, value: u16) -> bool {
+        match self {
+            Entry::Raw(view) => view.contains(value),
+            Entry::Runs(runs) => runs.contains(value),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Entry::Raw(view) => view.len(),
+            Entry::Runs(runs) => runs.iter().map(|(s, e)| (e - s) as usize + 1).sum(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self {
+            Entry::Raw(view) => view.is_empty(),
+            Entry::Runs(runs) => runs.0.is_empty(),
+        }
+    }
+
+    pub fn to_bitmap(&self) -> Bitmap {
+        match self {
+            Entry::Raw(view) => view.to_bitmap(),
+            Entry::Runs(runs) => {
+                let mut bitmap = Bitmap::new();
+                for (start, end) in runs.iter() {
+                    for value in start..=end {
+                        bitmap.insert_unchecked(value);
+                    }
+                }
+                bitmap.recompute_len();
+                bitmap
+            }
+        }
+    }
+}
+
+/// The runs of consecutive values of a bitmap, as stored in a [`BitmapFile`].
+#[derive(Clone, Copy)]
+pub struct Runs<'a>(&'a [u8]);
+
+impl Runs<'_> {
+    fn run(&self, i: usize) -> (u16, u16) {
+        let run = &self.0[i * RUN_SIZE..][..RUN_SIZE];
+        (
+            u16::from_le_bytes([run[0], run[1]]),
+            u16::from_le_bytes([run[2], run[3]]),
+        )
+    }
+
+    /// Iterates over the runs as inclusive bounds.
+    pub fn iter(&self) -> impl Iterator<Item = (u16, u16)> + '_ {
+        (0..self.0.len() / RUN_SIZE).map(|i| self.run(i))
+    }
+
+    /// Binary search the run that could contain `value`.
+    pub fn contains(&self, value: u16) -> bool {
+        let (mut low, mut high) = (0, self.0.len() / RUN_SIZE);
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let (start, end) = self.run(mid);
+            if value < start {
+                high = mid;
+            } else if value > end {
+                low = mid + 1;
+            } else {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    fn write(bitmaps: &[Bitmap]) -> Vec<u8> {
+        let mut writer = BitmapFileWriter::new(Vec::new());
+        for (i, bitmap) in bitmaps.iter().enumerate() {
+            assert_eq!(writer.push(bitmap).unwrap(), i);
+        }
+        writer.finish().unwrap()
+    }
+
+    #[test]
+    fn file() {
+        let bitmaps = [
+            Bitmap::from_iter((0..1000).chain(2000..3000)),
+            Bitmap::new(),
+            Bitmap::from_iter((0..=u16::MAX).step_by(3)),
+            Bitmap::from_iter([u16::MAX]),
+        ];
+        let bytes = write(&bitmaps);
+        let file = BitmapFile::open(&bytes).unwrap();
+        assert_eq!(file.len(), 4);
+
+        let entries: Vec<_> = file.iter().collect::<Result<_, _>>().unwrap();
+        assert_eq!(entries.len(), 4);
+        assert!(matches!(entries[0], Entry::Runs(_)));
+        assert!(matches!(entries[1], Entry::Runs(_)));
+        assert!(matches!(entries[2], Entry::Raw(_)));
+        assert!(matches!(entries[3], Entry::Runs(_)));
+        for (entry, bitmap) in entries.iter().zip(&bitmaps) {
+            assert_eq!(&entry.to_bitmap(), bitmap);
+            assert_eq!(entry.len(), bitmap.len());
+            assert_eq!(entry.is_empty(), bitmap.is_empty());
+        }
+        assert!(entries[0].contains(2500));
+        assert!(!entries[0].contains(1000));
+        assert!(entries[2].contains(3));
+        assert!(entries[3].contains(u16::MAX));
+        assert!(file.get(4).is_none());
+        assert!(file.get(usize::MAX / 2).is_none());
+        assert!(file.get(usize::MAX).is_none());
+
+        // the raw entries are aligned in the file
+        match entries[2] {
+            Entry::Raw(view) => {
+                let offset = view.as_bytes().as_ptr() as usize - bytes.as_ptr() as usize;
+                assert_eq!(offset % RAW_ALIGNMENT as usize, 0);
+            }
+            Entry::Runs(_) => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn corrupted() {
+        let bytes = write(&[Bitmap::from_iter([1, 2, 3])]);
+        assert_eq!(
+            BitmapFile::open(&bytes[..bytes.len() - 1]).err(),
+            Some(Error::InvalidMagic)
+        );
+        assert_eq!(
+            BitmapFile::open(&bytes[1..]).err(),
+            Some(Error::Corrupted("the index doesn't end at the footer"))
+        );
+        assert_eq!(
+            BitmapFile::open(&[]).err(),
+            Some(Error::InvalidLength {
+                expected: FOOTER_SIZE,
+                found: 0
+            })
+        );
+
+        // a raw entry moved by one byte
+        let mut bytes = write(&[Bitmap::from_iter((0..=u16::MAX).step_by(3))]);
+        let index = bytes.len() - FOOTER_SIZE - INDEX_ENTRY_SIZE;
+        bytes[index] = 1;
+        let file = BitmapFile::open(&bytes).unwrap();
+        assert_eq!(
+            file.get(0).unwrap().err(),
+            Some(Error::Misaligned { offset: 1 })
+        );
+
+        bytes[index] = 0;
+        bytes[index + 12] = 42;
+        let file = BitmapFile::open(&bytes).unwrap();
+        assert_eq!(
+            file.iter().next().unwrap().err(),
+            Some(Error::UnknownEncoding(42))
+        );
+
+        // the runs `1..=3` and `10..=11`, stored first in the file
+        let mut bytes = write(&[Bitmap::from_iter([1, 2, 3, 10, 11])]);
+        assert!(BitmapFile::open(&bytes).unwrap().get(0).unwrap().is_ok());
+        bytes[2..4].copy_from_slice(&0_u16.to_le_bytes());
+        assert_eq!(
+            BitmapFile::open(&bytes).unwrap().get(0).unwrap().err(),
+            Some(Error::Corrupted("a run ends before it starts"))
+        );
+        bytes[2..4].copy_from_slice(&3_u16.to_le_bytes());
+        bytes[4..6].copy_from_slice(&2_u16.to_le_bytes());
+        assert_eq!(
+            BitmapFile::open(&bytes).unwrap().get(0).unwrap().err(),
+            Some(Error::Corrupted("the runs are unsorted or overlap"))
+        );
+
+        let empty = write(&[]);
+        let file = BitmapFile::open(&empty).unwrap();
+        assert!(file.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_roundtrip(bitmaps in prop::collection::vec(crate::strategies::any(), 0..5)) {
+            let bytes = write(&bitmaps);
+            let file = BitmapFile::open(&bytes).unwrap();
+            assert_eq!(file.len(), bitmaps.len());
+            for (entry, bitmap) in file.iter().zip(&bitmaps) {
+                assert_eq!(&entry.unwrap().to_bitmap(), bitmap);
+            }
+        }
+    }
+}
@@ -0,0 +1,89 @@
+//! A hash of the bitmaps that is the same on every platform and every version
+//! of the crate: the [XXH64](https://github.com/Cyan4973/xxHash/blob/dev/doc/xxhash_spec.md)
+//! hash of the raw encoding returned by [`Bitmap::to_bytes`].
+
+use crate::Bitmap;
+
+const PRIME_1: u64 = 0x9E37_79B1_85EB_CA87;
+const PRIME_2: u64 = 0xC2B2_AE3D_27D4_EB4F;
+const PRIME_3: u64 = 0x1656_67B1_9E37_79F9;
+const PRIME_4: u64 = 0x85EB_CA77_C2B2_AE63;
+
+#[inline]
+fn round(acc: u64, lane: u64) -> u64 {
+    acc.wrapping_add(lane.wrapping_mul(PRIME_2))
+        .rotate_left(31)
+        .wrapping_mul(PRIME_1)
+}
+
+#[inline]
+fn merge(acc: u64, lane: u64) -> u64 {
+    (acc ^ round(0, lane))
+        .wrapping_mul(PRIME_1)
+        .wrapping_add(PRIME_4)
+}
+
+impl Bitmap {
+    /// Returns the XXH64 hash (with a seed of 0) of the raw encoding of the bitmap.
+    ///
+    /// Unlike the [`Hash`](core::hash::Hash) implementation, the fingerprint is
+    /// stable across platforms, thus it can be used as a cache key or to check
+    /// two replicas contain the same values.
+    pub fn fingerprint(&self) -> u64 {
+        // The encoding is the words in little endian, the lanes of 8 bytes
+        // read by XXH64 are thus the words themselves. It's a multiple of 32
+        // bytes so there is no remainder to process.
+        let mut acc = [
+            PRIME_1.wrapping_add(PRIME_2),
+            PRIME_2,
+            0,
+            0_u64.wrapping_sub(PRIME_1),
+        ];
+        let (stripes, []) = self.store.as_chunks::<4>() else {
+            unreachable!()
+        };
+        for stripe in stripes {
+            for (acc, lane) in acc.iter_mut().zip(stripe) {
+                *acc = round(*acc, *lane);
+            }
+        }
+
+        let mut hash = acc[0]
+            .rotate_left(1)
+            .wrapping_add(acc[1].rotate_left(7))
+            .wrapping_add(acc[2].rotate_left(12))
+            .wrapping_add(acc[3].rotate_left(18));
+        for lane in acc {
+            hash = merge(hash, lane);
+        }
+        hash = hash.wrapping_add(Self::RAW_SIZE as u64);
+
+        hash ^= hash >> 33;
+        hash = hash.wrapping_mul(PRIME_2);
+        hash ^= hash >> 29;
+        hash = hash.wrapping_mul(PRIME_3);
+        hash ^ (hash >> 32)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn fingerprint() {
+        // these values must never change
+        insta::assert_snapshot!(format!("{:#x}", Bitmap::new().fingerprint()), @"0x2b5073505a48fb4");
+        insta::assert_snapshot!(format!("{:#x}", Bitmap::full().fingerprint()), @"0xb746f89938b7b253");
+        insta::assert_snapshot!(format!("{:#x}", Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]).fingerprint()), @"0xfe571e688414b0b7");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_fingerprint(bitmap in crate::strategies::any()) {
+            let expected = twox_hash::XxHash64::oneshot(0, &bitmap.to_bytes());
+            assert_eq!(bitmap.fingerprint(), expected);
+        }
+    }
+}
@@ -0,0 +1,142 @@
+use crate::Bitmap;
+
+/// Counts the modifications of a bitmap with the `generation` feature, a
+/// zero-sized no-op otherwise. Every method modifying a bitmap bumps it.
+#[derive(Default, Clone)]
+pub(crate) struct Generation {
+    #[cfg(feature = "generation")]
+    count: u64,
+}
+
+impl Generation {
+    #[inline]
+    pub(crate) const fn new() -> Self {
+        Generation {
+            #[cfg(feature = "generation")]
+            count: 0,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn bump(&mut self) {
+        #[cfg(feature = "generation")]
+        {
+            self.count = self.count.wrapping_add(1);
+        }
+    }
+
+    /// Always `0` without the `generation` feature.
+    #[inline]
+    pub(crate) fn get(&self) -> u64 {
+        #[cfg(feature = "generation")]
+        return self.count;
+        #[cfg(not(feature = "generation"))]
+        0
+    }
+}
+
+/// A position in a bitmap that doesn't borrow it, see [`Bitmap::cursor`].
+///
+/// With the `generation` feature and debug assertions enabled, using a cursor
+/// on a bitmap modified since the cursor was created or [synced](Cursor::sync)
+/// panics instead of silently yielding values from the new content.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cursor {
+    /// The smallest value that can still be yielded, `65536` once exhausted.
+    next: u32,
+    generation: u64,
+}
+
+impl Cursor {
+    /// Returns the next value of `bitmap` after the last one yielded.
+    ///
+    /// # Panics
+    ///
+    /// With the `generation` feature in debug builds, if `bitmap` was modified
+    /// since the cursor was created or synced.
+    pub fn next(&mut self, bitmap: &Bitmap) -> Option<u16> {
+        self.check(bitmap);
+        let from = u16::try_from(self.next).ok()?;
+        match bitmap.iter_range(from..).next() {
+            Some(value) => {
+                self.next = value as u32 + 1;
+                Some(value)
+            }
+            None => {
+                self.next = u16::MAX as u32 + 1;
+                None
+            }
+        }
+    }
+
+    /// Acknowledges the modifications of `bitmap`, the cursor keeps its position.
+    pub fn sync(&mut self, bitmap: &Bitmap) {
+        self.generation = bitmap.generation.get();
+    }
+
+    #[inline]
+    fn check(&self, bitmap: &Bitmap) {
+        if cfg!(debug_assertions) {
+            let generation = bitmap.generation.get();
+            assert_eq!(
+                self.generation, generation,
+                "the bitmap was modified since the cursor was created (generation {generation} instead of {})",
+                self.generation
+            );
+        }
+    }
+}
+
+impl Bitmap {
+    /// Returns a cursor over the values of the bitmap that doesn't keep it borrowed,
+    /// which lets the bitmap be modified between two values.
+    ///
+    /// Enable the `generation` feature to make the cursor panic in debug builds
+    /// when that happens without a call to [`Cursor::sync`].
+    pub fn cursor(&self) -> Cursor {
+        Cursor {
+            next: 0,
+            generation: self.generation.get(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn cursor() {
+        let mut bitmap = Bitmap::from_iter([3, 64, u16::MAX]);
+        let mut cursor = bitmap.cursor();
+        assert_eq!(cursor.next(&bitmap), Some(3));
+
+        bitmap.remove(64);
+        bitmap.insert(90);
+        cursor.sync(&bitmap);
+        assert_eq!(cursor.next(&bitmap), Some(90));
+        assert_eq!(cursor.next(&bitmap), Some(u16::MAX));
+        assert_eq!(cursor.next(&bitmap), None);
+        assert_eq!(cursor.next(&bitmap), None);
+    }
+
+    #[cfg(all(feature = "generation", debug_assertions))]
+    #[test]
+    #[should_panic(expected = "the bitmap was modified since the cursor was created")]
+    fn cursor_detects_modifications() {
+        let mut bitmap = Bitmap::from_iter([3, 64]);
+        let mut cursor = bitmap.cursor();
+        cursor.next(&bitmap);
+        bitmap.clear();
+        cursor.next(&bitmap);
+    }
+
+    #[cfg(feature = "generation")]
+    #[test]
+    fn bumped_by_whole_word_operations() {
+        let mut bitmap = Bitmap::from_iter([3, 64]);
+        let before = bitmap.generation.get();
+        bitmap.intersection(&Bitmap::full());
+        assert!(bitmap.generation.get() > before);
+    }
+}
@@ -0,0 +1,169 @@
+//! Directed graphs over the `u16` node space, where the neighbors of every
+//! node are stored in a [`Bitmap`] called its adjacency row.
+//!
+//! The traversals only manipulate sets of nodes, thus they're expressed with
+//! bitmap algebra: the next frontier of a BFS is the union of the rows of the
+//! current frontier minus the nodes already visited.
+
+use crate::Bitmap;
+
+/// The rows are only allocated up to the greatest node with neighbors.
+#[derive(Clone, Default)]
+pub struct Graph {
+    rows: Vec<Bitmap>,
+}
+
+impl Graph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a graph where the node `i` points to the nodes of `rows[i]`.
+    ///
+    /// # Panics
+    ///
+    /// If there are more than 65536 rows.
+    pub fn from_rows(rows: Vec<Bitmap>) -> Self {
+        assert!(
+            rows.len() <= u16::MAX as usize + 1,
+            "a graph can't have more than 65536 nodes, got {} rows",
+            rows.len()
+        );
+        Graph { rows }
+    }
+
+    /// Add an edge from `from` to `to`.
+    /// Returns `true` if the edge was not already present in the graph.
+    pub fn add_edge(&mut self, from: u16, to: u16) -> bool {
+        let from = from as usize;
+        if self.rows.len() <= from {
+            self.rows.resize_with(from + 1, Bitmap::new);
+        }
+        self.rows[from].insert(to)
+    }
+
+    /// Remove the edge from `from` to `to`.
+    /// Returns `true` if the edge was present in the graph.
+    pub fn remove_edge(&mut self, from: u16, to: u16) -> bool {
+        self.rows
+            .get_mut(from as usize)
+            .is_some_and(|row| row.remove(to))
+    }
+
+    /// Returns the nodes `node` points to.
+    pub fn neighbors(&self, node: u16) -> &Bitmap {
+        static EMPTY: Bitmap = Bitmap::new();
+        self.rows.get(node as usize).unwrap_or(&EMPTY)
+    }
+
+    /// Returns the nodes pointed to by any node of the `frontier`.
+    pub fn expand(&self, frontier: &Bitmap) -> Bitmap {
+        let mut next = Bitmap::new();
+        frontier.visit(|node| {
+            if let Some(row) = self.rows.get(node as usize) {
+                for (word, neighbors) in next.store.iter_mut().zip(&row.store) {
+                    *word |= neighbors;
+                }
+            }
+        });
+        next.recompute_len();
+        next
+    }
+
+    /// Returns the levels of a BFS starting from all the nodes of `start` at
+    /// once: the first level is `start` itself, and every level holds the
+    /// nodes at the next distance that weren't in any previous level.
+    /// The empty levels aren't returned.
+    pub fn bfs_levels(&self, start: &Bitmap) -> Vec<Bitmap> {
+        let mut visited = start.clone();
+        let mut levels = Vec::new();
+        let mut frontier = start.clone();
+        while !frontier.is_empty() {
+            let mut next = self.expand(&frontier);
+            for (word, visited) in next.store.iter_mut().zip(&mut visited.store) {
+                *word &= !*visited;
+                *visited |= *word;
+            }
+            next.recompute_len();
+            levels.push(core::mem::replace(&mut frontier, next));
+        }
+        levels
+    }
+
+    /// Returns all the nodes reachable from `start`, including `start` itself.
+    pub fn reachable(&self, start: &Bitmap) -> Bitmap {
+        self.bfs_levels(start)
+            .into_iter()
+            .fold(Bitmap::new(), |reachable, level| reachable | &level)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn graph() {
+        let mut graph = Graph::new();
+        assert!(graph.add_edge(0, 1));
+        assert!(!graph.add_edge(0, 1));
+        graph.add_edge(0, 2);
+        graph.add_edge(1, 3);
+        graph.add_edge(2, 3);
+        graph.add_edge(3, 0);
+        graph.add_edge(4, u16::MAX);
+        assert!(graph.remove_edge(4, u16::MAX));
+        assert!(!graph.remove_edge(u16::MAX, 4));
+        assert!(graph.neighbors(u16::MAX).is_empty());
+
+        let start = Bitmap::from_iter([0]);
+        assert_eq!(graph.expand(&start).to_vec(), [1, 2]);
+        let levels: Vec<Vec<u16>> = graph
+            .bfs_levels(&start)
+            .iter()
+            .map(Bitmap::to_vec)
+            .collect();
+        assert_eq!(levels, [vec![0], vec![1, 2], vec![3]]);
+        assert_eq!(
+            graph.reachable(&Bitmap::from_iter([3])).to_vec(),
+            [0, 1, 2, 3]
+        );
+        assert_eq!(graph.reachable(&Bitmap::from_iter([4])).to_vec(), [4]);
+        assert!(graph.bfs_levels(&Bitmap::new()).is_empty());
+    }
+
+    #[test]
+    #[should_panic = "a graph can't have more than 65536 nodes, got 65537 rows"]
+    fn too_many_rows() {
+        Graph::from_rows(vec![Bitmap::new(); u16::MAX as usize + 2]);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_bfs(edges in prop::collection::vec((0..50_u16, 0..50_u16), 0..100), start in 0..50_u16) {
+            let mut graph = Graph::new();
+            for (from, to) in &edges {
+                graph.add_edge(*from, *to);
+            }
+            // the distances computed one node at a time
+            let mut distances = vec![usize::MAX; 50];
+            distances[start as usize] = 0;
+            let mut queue = std::collections::VecDeque::from([start]);
+            while let Some(node) = queue.pop_front() {
+                for next in graph.neighbors(node) {
+                    if distances[next as usize] == usize::MAX {
+                        distances[next as usize] = distances[node as usize] + 1;
+                        queue.push_back(next);
+                    }
+                }
+            }
+            for (distance, level) in graph.bfs_levels(&Bitmap::from_iter([start])).iter().enumerate() {
+                let expected: Vec<u16> = (0..50).filter(|node| distances[*node as usize] == distance).collect();
+                assert_eq!(level.to_vec(), expected);
+            }
+            let reachable: Vec<u16> = (0..50).filter(|node| distances[*node as usize] != usize::MAX).collect();
+            assert_eq!(graph.reachable(&Bitmap::from_iter([start])).to_vec(), reachable);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::Bitmap;
+
+/// Deduplicates the bitmaps containing the same values: interning a bitmap
+/// returns a shared handle on the first bitmap equal to it that was interned.
+#[derive(Default)]
+pub struct Interner {
+    bitmaps: HashSet<Arc<Bitmap>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Interner::default()
+    }
+
+    /// Returns the shared handle of the bitmap, the bitmap is dropped if an
+    /// equal one was already interned.
+    pub fn intern(&mut self, bitmap: Bitmap) -> Arc<Bitmap> {
+        if let Some(shared) = self.bitmaps.get(&bitmap) {
+            return shared.clone();
+        }
+        let shared = Arc::new(bitmap);
+        self.bitmaps.insert(shared.clone());
+        shared
+    }
+
+    /// Same as [`Interner::intern`] but the bitmap is only cloned if it was never interned.
+    pub fn intern_ref(&mut self, bitmap: &Bitmap) -> Arc<Bitmap> {
+        match self.bitmaps.get(bitmap) {
+            Some(shared) => shared.clone(),
+            None => self.intern(bitmap.clone()),
+        }
+    }
+
+    /// Returns the shared handle of the bitmap if an equal one was interned.
+    pub fn get(&self, bitmap: &Bitmap) -> Option<Arc<Bitmap>> {
+        self.bitmaps.get(bitmap).cloned()
+    }
+
+    /// Number of distinct bitmaps interned.
+    pub fn len(&self) -> usize {
+        self.bitmaps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bitmaps.is_empty()
+    }
+
+    /// Forgets the bitmaps that are only referenced by the interner and
+    /// returns how many were dropped.
+    pub fn collect_unused(&mut self) -> usize {
+        let before = self.bitmaps.len();
+        self.bitmaps.retain(|shared| Arc::strong_count(shared) > 1);
+        before - self.bitmaps.len()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn interner() {
+        let mut interner = Interner::new();
+        let a = interner.intern(Bitmap::from_iter([1, 2, 3]));
+        let b = interner.intern(Bitmap::from_iter([3, 2, 1]));
+        let c = interner.intern_ref(&Bitmap::from_iter([u16::MAX]));
+        assert!(Arc::ptr_eq(&a, &b));
+        assert!(!Arc::ptr_eq(&a, &c));
+        assert_eq!(interner.len(), 2);
+        assert!(Arc::ptr_eq(
+            &interner.get(&Bitmap::from_iter([1, 2, 3])).unwrap(),
+            &a
+        ));
+        assert!(interner.get(&Bitmap::new()).is_none());
+
+        drop(c);
+        assert_eq!(interner.collect_unused(), 1);
+        assert_eq!(interner.len(), 1);
+        drop((a, b));
+        assert_eq!(interner.collect_unused(), 1);
+        assert!(interner.is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_interner(bitmaps in prop::collection::vec(prop::sample::select(vec![0_u16, 1, 64, 1000, u16::MAX]), 0..20)) {
+            let mut interner = Interner::new();
+            let handles: Vec<Arc<Bitmap>> = bitmaps
+                .iter()
+                .map(|value| interner.intern(Bitmap::from_iter([*value])))
+                .collect();
+            let mut distinct = bitmaps.clone();
+            distinct.sort_unstable();
+            distinct.dedup();
+            assert_eq!(interner.len(), distinct.len());
+            for (handle, value) in handles.iter().zip(&bitmaps) {
+                assert_eq!(handle.to_vec(), [*value]);
+                assert!(Arc::ptr_eq(handle, &interner.get(handle).unwrap()));
+            }
+        }
+    }
+}
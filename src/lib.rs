@@ -1,21 +1,136 @@
+#![cfg_attr(kernel = "portable", feature(portable_simd))]
+
+// lets the macros refer to `::bitmap` from the tests of this crate
+#[cfg(all(test, feature = "macros"))]
+extern crate self as bitmap;
+
 use core::fmt;
 
+use backend::Op;
+use generation::Generation;
+
+mod appender;
+mod arithmetic;
+#[cfg(feature = "arrow")]
+mod arrow;
+#[cfg(feature = "autotune")]
+mod autotune;
+#[cfg(kernel = "avx2")]
+mod avx2;
+mod backend;
+mod bit_ref;
+mod bitmap_vec;
+mod checked;
+mod complement;
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+mod compression;
+mod constant_time;
+mod counting;
+mod cover;
+pub mod crdt;
+#[cfg(feature = "croaring")]
+mod croaring;
+mod enum_bitmap;
+mod error;
+#[cfg(feature = "uniffi")]
+mod ffi;
+pub mod file;
+mod fingerprint;
+mod generation;
+pub mod graph;
+mod interner;
+mod iter;
+#[cfg(kernel = "neon")]
+mod neon;
+#[cfg(feature = "observer")]
+mod observer;
+mod offset;
+mod ops;
+mod persistent;
+#[cfg(kernel = "portable")]
+mod portable;
+mod prefixes;
+mod ranges;
+mod ranked;
+#[cfg(feature = "rayon")]
+mod rayon;
+mod score;
+mod select;
+#[cfg(feature = "serde")]
+mod serde;
+mod serialization;
+mod set;
+mod sharded;
+mod shuffle;
+mod sketch;
+mod slice;
+mod slotmap;
+#[cfg(any(test, feature = "proptest"))]
+pub mod strategies;
+#[cfg(feature = "succinct")]
+mod succinct;
+mod swar;
+mod varint;
+mod view;
+
+pub use appender::BitmapAppender;
+pub use bit_ref::BitRef;
+#[cfg(feature = "macros")]
+pub use bitmap_macros::include_bitmap;
+pub use bitmap_vec::BitmapVec;
+pub use complement::ComplementView;
+#[cfg(any(feature = "zstd", feature = "lz4"))]
+pub use compression::Compression;
+pub use counting::CountingBitmap;
+pub use enum_bitmap::EnumBitmap;
+pub use error::Error;
+#[cfg(feature = "uniffi")]
+pub use ffi::FfiBitmap;
+pub use file::{BitmapFile, BitmapFileWriter};
+pub use generation::Cursor;
+pub use interner::Interner;
+pub use iter::{EitherOrBoth, Iter};
+#[cfg(feature = "observer")]
+pub use observer::{Change, ObservedBitmap};
+pub use offset::OffsetBitmap;
+pub use ops::BitmapOps;
+pub use persistent::PersistentBitmap;
+pub use prefixes::Bitmap8;
+pub use ranked::RankedBitmap;
+#[cfg(feature = "rayon")]
+pub use rayon::ParIter;
+pub use sharded::ShardedBitmap;
+pub use shuffle::Shuffled;
+pub use sketch::SmallSketch;
+pub use slice::{BitmapSlice, BitmapSliceBuf};
+pub use slotmap::SlotMap16;
+pub use view::BitmapView;
+
+#[cfg(feature = "uniffi")]
+uniffi::setup_scaffolding!();
+
 type Word = u64;
 
 #[derive(Clone)]
 pub struct Bitmap {
     len: usize,
     store: [Word; Self::BITMAP_SIZE],
+    generation: Generation,
 }
 
 impl Bitmap {
     const BITMAP_SIZE: usize = (u16::MAX as usize + 1) / Word::BITS as usize;
 
+    /// Maximum number of values printed by the `Debug` implementation when no
+    /// precision is specified.
+    pub const DEBUG_LIMIT: usize = 100;
+
     #[inline]
     pub const fn new() -> Self {
         Bitmap {
             len: 0,
             store: [0; Self::BITMAP_SIZE],
+            generation: Generation::new(),
         }
     }
 
@@ -24,6 +139,22 @@ impl Bitmap {
         Bitmap {
             len: u16::MAX as usize + 1,
             store: [Word::MAX; Self::BITMAP_SIZE],
+            generation: Generation::new(),
+        }
+    }
+
+    /// Builds a bitmap from the words of its store, see [`Bitmap::internal_store`].
+    pub const fn from_words(words: [Word; Self::BITMAP_SIZE]) -> Self {
+        let mut len = 0;
+        let mut key = 0;
+        while key < Self::BITMAP_SIZE {
+            len += words[key].count_ones() as usize;
+            key += 1;
+        }
+        Bitmap {
+            len,
+            store: words,
+            generation: Generation::new(),
         }
     }
 
@@ -32,6 +163,58 @@ impl Bitmap {
         &self.store
     }
 
+    /// Reduce the words of the store in order, to write custom operations at
+    /// the word granularity.
+    /// ```
+    /// use bitmap::Bitmap;
+    ///
+    /// let bitmap = Bitmap::from_iter([1, 2, 64]);
+    /// let non_empty_words = bitmap.fold_words(0, |acc, word| acc + (word != 0) as usize);
+    /// assert_eq!(non_empty_words, 2);
+    /// ```
+    #[inline]
+    pub fn fold_words<T>(&self, init: T, f: impl FnMut(T, Word) -> T) -> T {
+        self.store.iter().copied().fold(init, f)
+    }
+
+    /// Reduce the words of two bitmaps side by side, the closure receives the
+    /// words of `self` and `other` at the same position.
+    #[inline]
+    pub fn fold_words_with<T>(
+        &self,
+        other: &Self,
+        init: T,
+        mut f: impl FnMut(T, Word, Word) -> T,
+    ) -> T {
+        self.store
+            .iter()
+            .zip(&other.store)
+            .fold(init, |acc, (l, r)| f(acc, *l, *r))
+    }
+
+    /// Replaces every word of `self` by `f` applied to it and the word of
+    /// `other` at the same position, then updates the length. It's meant for
+    /// the operations without a dedicated method.
+    /// ```
+    /// use bitmap::Bitmap;
+    ///
+    /// let mut bitmap = Bitmap::from_iter([1, 2, 64]);
+    /// // keeps the values of `bitmap` absent from the other one
+    /// bitmap.combine_with(&Bitmap::from_iter([2, 3]), |l, r| l & !r);
+    /// assert_eq!(bitmap.to_vec(), [1, 64]);
+    /// ```
+    #[inline]
+    pub fn combine_with(&mut self, other: &Self, mut f: impl FnMut(Word, Word) -> Word) {
+        let mut count = 0;
+        for (left, right) in self.store.iter_mut().zip(&other.store) {
+            *left = f(*left, *right);
+            count += left.count_ones();
+        }
+        self.len = count as usize;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
     #[inline]
     pub fn len(&self) -> usize {
         self.len
@@ -42,117 +225,420 @@ impl Bitmap {
         self.len() == 0
     }
 
+    /// Panics if the internal state of the bitmap is inconsistent, i.e. if the
+    /// cached length doesn't match the number of bits set in the store.
+    pub fn assert_invariants(&self) {
+        let count: usize = self.store.iter().map(|w| w.count_ones() as usize).sum();
+        assert_eq!(
+            self.len, count,
+            "the bitmap length ({}) doesn't match the number of values in the store ({count})",
+            self.len
+        );
+    }
+
+    /// Check the invariants of the bitmap in debug builds only, meant to be
+    /// called after any operation updating the length from whole words.
+    #[inline]
+    pub(crate) fn debug_assert_invariants(&self) {
+        if cfg!(debug_assertions) {
+            self.assert_invariants();
+        }
+    }
+
     #[inline]
     fn key(index: u16) -> usize {
         index as usize / Word::BITS as usize
     }
 
     #[inline]
-    fn bit(index: u16) -> usize {
+    fn bit_offset(index: u16) -> usize {
         index as usize % Word::BITS as usize
     }
 
     /// Returns `true` if the value was already present in the bitmap.
     #[inline]
     pub fn insert(&mut self, value: u16) -> bool {
-        let (key, bit) = (Self::key(value), Self::bit(value));
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
         let old_w = self.store[key];
         let new_w = old_w | 1 << bit;
         let inserted = (old_w ^ new_w) >> bit;
         self.store[key] = new_w;
         self.len += inserted as usize;
+        self.generation.bump();
         inserted != 0
     }
 
+    /// Insert a value without updating the length of the bitmap.
+    ///
+    /// This is meant for bulk loaders: once all the values have been inserted
+    /// [`Bitmap::recompute_len`] **must** be called. Until then, the length
+    /// of the bitmap is wrong and every method relying on it (`len`, `to_vec`,
+    /// `Debug`, `==`, ...) returns garbage. The debug builds will panic if any
+    /// operation checking the invariants of the bitmap is called in between.
+    #[inline]
+    pub fn insert_unchecked(&mut self, value: u16) {
+        self.store[Self::key(value)] |= 1 << Self::bit_offset(value);
+        self.generation.bump();
+    }
+
+    /// Recompute the length of the bitmap from its store with a single popcount pass.
+    /// Must be called after a batch of [`Bitmap::insert_unchecked`].
+    pub fn recompute_len(&mut self) {
+        self.len = if swar::HARDWARE_POPCOUNT || cfg!(kernel = "scalar") {
+            self.store
+                .iter()
+                .map(|word| word.count_ones() as usize)
+                .sum()
+        } else {
+            swar::count_ones(&self.store)
+        };
+        self.generation.bump();
+    }
+
     /// Returns `true` if the value was already present in the bitmap.
     #[inline]
     pub fn remove(&mut self, value: u16) -> bool {
-        let (key, bit) = (Self::key(value), Self::bit(value));
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
         let old_w = self.store[key];
         let new_w = old_w & !(1 << bit);
         let removed = (old_w ^ new_w) >> bit;
         self.store[key] = new_w;
         self.len -= removed as usize;
+        self.generation.bump();
         removed != 0
     }
 
     /// Returns `true` if the value was present in the bitmap.
     #[inline]
     pub fn contains(&self, index: u16) -> bool {
-        self.store[Self::key(index)] & (1 << Self::bit(index)) != 0
+        self.store[Self::key(index)] & (1 << Self::bit_offset(index)) != 0
+    }
+
+    /// Returns the smallest value absent from the bitmap,
+    /// or `None` if the bitmap is full.
+    #[inline]
+    pub fn first_absent(&self) -> Option<u16> {
+        self.next_absent(0)
+    }
+
+    /// Returns the smallest value greater or equal to `from` absent from the
+    /// bitmap, or `None` if all these values are present.
+    #[inline]
+    pub fn next_absent(&self, from: u16) -> Option<u16> {
+        let (key, bit) = (Self::key(from), Self::bit_offset(from));
+        // consider the values lower than `from` in its word as present
+        let first = self.store[key] | ((1 << bit) - 1);
+        core::iter::once(first)
+            .chain(self.store[key + 1..].iter().copied())
+            .position(|word| word != Word::MAX)
+            .map(|offset| {
+                let key = key + offset;
+                let word = if offset == 0 { first } else { self.store[key] };
+                (key * Word::BITS as usize + word.trailing_ones() as usize) as u16
+            })
     }
 
     #[inline]
     pub fn intersection(&mut self, other: &Self) {
+        self.intersection_with(other, backend::Scalar);
+    }
+
+    /// Adds all the values of `other` to `self`.
+    #[inline]
+    pub fn union(&mut self, other: &Self) {
+        self.binary_with(other, Op::Or, backend::Scalar);
+    }
+
+    /// Removes all the values of `other` from `self`.
+    #[inline]
+    pub fn difference(&mut self, other: &Self) {
+        self.binary_with(other, Op::AndNot, backend::Scalar);
+    }
+
+    /// Keeps the values present in either `self` or `other` but not in both.
+    #[inline]
+    pub fn symmetric_difference(&mut self, other: &Self) {
+        self.binary_with(other, Op::Xor, backend::Scalar);
+    }
+
+    /// Moves all the values of `other` into `self`, leaving `other` empty.
+    #[inline]
+    pub fn append(&mut self, other: &mut Self) {
+        self.union(other);
+        other.clear();
+    }
+
+    /// Computes `(self & b) | c` in a single pass over the stores.
+    #[inline]
+    pub fn and_or(&mut self, b: &Self, c: &Self) {
         let mut count = 0;
         for index in 0..self.store.len() {
-            self.store[index] &= other.store[index];
+            self.store[index] = (self.store[index] & b.store[index]) | c.store[index];
             count += self.store[index].count_ones();
         }
         self.len = count as usize;
+        self.generation.bump();
+        self.debug_assert_invariants();
     }
 
+    /// Computes `(self | b) & c` in a single pass over the stores.
     #[inline]
-    pub fn intersection_simd(&mut self, other: &Self) {
-        use core::arch::aarch64::*;
+    pub fn or_and(&mut self, b: &Self, c: &Self) {
+        let mut count = 0;
+        for index in 0..self.store.len() {
+            self.store[index] = (self.store[index] | b.store[index]) & c.store[index];
+            count += self.store[index].count_ones();
+        }
+        self.len = count as usize;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Computes `(self & !b) | c` in a single pass over the stores.
+    #[inline]
+    pub fn and_not_or(&mut self, b: &Self, c: &Self) {
+        let mut count = 0;
+        for index in 0..self.store.len() {
+            self.store[index] = (self.store[index] & !b.store[index]) | c.store[index];
+            count += self.store[index].count_ones();
+        }
+        self.len = count as usize;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
 
-        let mut left = self.store.as_mut_ptr();
-        let mut right = other.store.as_ptr();
+    /// Returns the values present in all the `bitmaps`, or an empty bitmap if
+    /// there is none. The words of all the inputs are and-ed together and
+    /// counted once, a word is skipped as soon as it becomes empty.
+    pub fn intersection_many(bitmaps: &[&Bitmap]) -> Bitmap {
+        let mut ret = Bitmap::new();
+        if bitmaps.is_empty() {
+            return ret;
+        }
         let mut count = 0;
+        for (index, word) in ret.store.iter_mut().enumerate() {
+            *word = Word::MAX;
+            for bitmap in bitmaps {
+                *word &= bitmap.store[index];
+                if *word == 0 {
+                    break;
+                }
+            }
+            count += word.count_ones();
+        }
+        ret.len = count as usize;
+        ret.debug_assert_invariants();
+        ret
+    }
 
-        unsafe {
-            for _ in 0..(Self::BITMAP_SIZE / 2) {
-                // load the data into the register
-                let left_lane = vld1q_u64(left);
-                let right_lane = vld1q_u64(right);
+    /// Keeps only the values of the bitmap also present in `sorted`, which
+    /// must be sorted in increasing order but can contain duplicates.
+    ///
+    /// The words of the bitmap are masked with the values of `sorted` falling
+    /// in them, no other bitmap is built.
+    pub fn intersection_with_slice(&mut self, sorted: &[u16]) {
+        debug_assert!(sorted.is_sorted(), "the values must be sorted");
+        let mut next_key = 0;
+        let mut count = 0;
+        for (key, mask) in Self::slice_masks(sorted) {
+            self.store[next_key..key].fill(0);
+            self.store[key] &= mask;
+            count += self.store[key].count_ones();
+            next_key = key + 1;
+        }
+        self.store[next_key..].fill(0);
+        self.len = count as usize;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
 
-                let ret = vandq_u64(left_lane, right_lane);
-                vst1q_u64(left, ret);
+    /// Returns the number of values present in both the bitmap and `sorted`,
+    /// see [`Bitmap::intersection_with_slice`].
+    pub fn intersection_len_with_slice(&self, sorted: &[u16]) -> usize {
+        debug_assert!(sorted.is_sorted(), "the values must be sorted");
+        Self::slice_masks(sorted)
+            .map(|(key, mask)| (self.store[key] & mask).count_ones() as usize)
+            .sum()
+    }
 
-                // update the count
-                let p8_count = vcntq_u8(vreinterpretq_u8_u64(ret));
-                let p8_count = vaddvq_u8(p8_count);
-                count += p8_count as usize;
+    /// Iterates over the keys of the words containing the values of `sorted`,
+    /// along with the mask of these values.
+    fn slice_masks(sorted: &[u16]) -> impl Iterator<Item = (usize, Word)> + '_ {
+        sorted
+            .chunk_by(|l, r| Self::key(*l) == Self::key(*r))
+            .map(|values| {
+                let mask = values
+                    .iter()
+                    .fold(0, |mask, value| mask | 1 << Self::bit_offset(*value));
+                (Self::key(values[0]), mask)
+            })
+    }
 
-                // increase the ptr
-                left = left.add(2);
-                right = right.add(2);
+    /// Returns the values present in any of the `bitmaps`.
+    ///
+    /// The store is built one block of words at a time: the block of every
+    /// input is or-ed into the block of the result while it stays in the
+    /// cache, and the result is counted once at the end.
+    pub fn union_many(bitmaps: &[&Bitmap]) -> Bitmap {
+        /// Number of words of a block, 512 bytes.
+        const BLOCK_WORDS: usize = 64;
+
+        let mut ret = Bitmap::new();
+        for (block, words) in ret.store.chunks_exact_mut(BLOCK_WORDS).enumerate() {
+            let offset = block * BLOCK_WORDS;
+            for bitmap in bitmaps {
+                let other = &bitmap.store[offset..offset + BLOCK_WORDS];
+                for (word, other) in words.iter_mut().zip(other) {
+                    *word |= other;
+                }
             }
         }
+        ret.recompute_len();
+        ret
+    }
+
+    /// Returns the number of values present in `a`, `b` and `c` without
+    /// materializing their intersection.
+    #[inline]
+    pub fn intersection3_len(a: &Self, b: &Self, c: &Self) -> usize {
+        let mut count = 0;
+        for index in 0..a.store.len() {
+            count += (a.store[index] & b.store[index] & c.store[index]).count_ones();
+        }
+        count as usize
+    }
 
-        self.len = count;
+    /// Estimates the number of values present in both `self` and `other` by
+    /// only intersecting `sample_words` words evenly spread over the store and
+    /// extrapolating to the whole store. `sample_words` is clamped to `1..=1024`,
+    /// with 1024 words the result is exact.
+    ///
+    /// It's a quick selectivity estimate, the values are expected to be spread
+    /// uniformly: a bitmap holding all its values in a few words can be way off.
+    #[inline]
+    pub fn estimate_intersection_len(&self, other: &Self, sample_words: usize) -> usize {
+        let sample_words = sample_words.clamp(1, Self::BITMAP_SIZE);
+        let stride = Self::BITMAP_SIZE / sample_words;
+        let mut count = 0;
+        for index in (0..Self::BITMAP_SIZE).step_by(stride).take(sample_words) {
+            count += (self.store[index] & other.store[index]).count_ones() as usize;
+        }
+        count * Self::BITMAP_SIZE / sample_words
     }
 
-    pub fn to_vec(&self) -> Vec<u16> {
-        let mut ret = Vec::with_capacity(self.len);
-        let mut word = Vec::with_capacity(Word::BITS as usize);
-        let mut current_idx = 0_u16;
-
-        for mut current in self.store {
-            if current.count_ones() != 0 {
-                word.clear();
-                for _ in (0..Word::BITS).rev() {
-                    if current & 1 == 1 {
-                        word.push(current_idx);
-                    }
-                    current >>= 1;
-                    // When reaching the last byte this is going to overflow
-                    // but it's probably not an issue since we're at the end
-                    current_idx = current_idx.saturating_add(1);
-                }
-                ret.extend_from_slice(&word);
-            } else {
-                // this would panic if it was executed on the last word of the store
-                // but we should always enter either in the previous if, or the
-                // next one in the previous iteration of the loop.
-                current_idx += Word::BITS as u16;
+    /// Returns the number of values present in both `self` and every bitmap
+    /// of `others`, in the same order, e.g. to rank candidates against a query.
+    ///
+    /// `self` is streamed once: every block of its words is scored against all
+    /// the candidates while it's still in the cache, and its empty blocks are skipped.
+    pub fn intersection_len_many(&self, others: &[&Bitmap]) -> Vec<usize> {
+        Self::blocked_intersection_lens(&self.store, others.iter().map(|other| &other.store))
+    }
+
+    /// Counts the values shared by `query` and every row, block by block, see
+    /// [`Bitmap::intersection_len_many`] and [`BitmapVec::intersection_lens`].
+    pub(crate) fn blocked_intersection_lens<'a>(
+        query: &[Word; Self::BITMAP_SIZE],
+        rows: impl ExactSizeIterator<Item = &'a [Word; Self::BITMAP_SIZE]> + Clone,
+    ) -> Vec<usize> {
+        /// Number of words scored against all the rows at once, 512 bytes.
+        const BLOCK_WORDS: usize = 64;
+
+        let mut counts = vec![0; rows.len()];
+        for (block, words) in query.chunks_exact(BLOCK_WORDS).enumerate() {
+            if words.iter().all(|word| *word == 0) {
+                continue;
             }
-            if ret.len() == self.len {
-                break;
+            let offset = block * BLOCK_WORDS;
+            for (count, row) in counts.iter_mut().zip(rows.clone()) {
+                *count += words
+                    .iter()
+                    .zip(&row[offset..offset + BLOCK_WORDS])
+                    .map(|(left, right)| (left & right).count_ones() as usize)
+                    .sum::<usize>();
             }
         }
+        counts
+    }
+
+    /// Same as [`Bitmap::intersection`] but vectorized with the kernels
+    /// selected by the features of the crate. Without any vectorized kernel it
+    /// falls back to a SWAR implementation on the targets without a popcount
+    /// instruction, and to the scalar implementation on the other ones.
+    ///
+    /// With the `autotune` feature, the first call times the kernels on the
+    /// running machine and the fastest one is used from then on.
+    #[inline]
+    pub fn intersection_simd(&mut self, other: &Self) {
+        self.binary_simd(other, Op::And);
+    }
+
+    /// Same as [`Bitmap::union`] but vectorized, see [`Bitmap::intersection_simd`].
+    #[inline]
+    pub fn union_simd(&mut self, other: &Self) {
+        self.binary_simd(other, Op::Or);
+    }
+
+    /// Same as [`Bitmap::difference`] but vectorized, see [`Bitmap::intersection_simd`].
+    #[inline]
+    pub fn difference_simd(&mut self, other: &Self) {
+        self.binary_simd(other, Op::AndNot);
+    }
+
+    /// Same as [`Bitmap::symmetric_difference`] but vectorized, see [`Bitmap::intersection_simd`].
+    #[inline]
+    pub fn symmetric_difference_simd(&mut self, other: &Self) {
+        self.binary_simd(other, Op::Xor);
+    }
+
+    /// The binary operation with the kernel picked by the autotuning, if enabled.
+    #[inline]
+    fn binary_simd(&mut self, other: &Self, op: Op) {
+        #[cfg(feature = "autotune")]
+        match autotune::kernel(op) {
+            autotune::Kernel::Scalar => self.binary_with(other, op, backend::Scalar),
+            autotune::Kernel::Swar => self.binary_with(other, op, swar::Swar),
+            autotune::Kernel::Vectorized => self.binary_vectorized(other, op),
+        }
+        #[cfg(not(feature = "autotune"))]
+        self.binary_vectorized(other, op);
+    }
+
+    /// The binary operation with the kernel selected by the features of the crate.
+    #[inline]
+    fn binary_vectorized(&mut self, other: &Self, op: Op) {
+        #[cfg(kernel = "scalar")]
+        self.binary_with(other, op, backend::Scalar);
+        #[cfg(kernel = "portable")]
+        self.binary_with(other, op, portable::Portable);
+        #[cfg(kernel = "neon")]
+        self.binary_with(other, op, neon::Neon);
+        #[cfg(kernel = "avx2")]
+        match avx2::Avx2::detect() {
+            Some(avx2) => avx2.binary(self, other, op),
+            None => self.binary_fallback(other, op),
+        }
+        #[cfg(kernel = "default")]
+        self.binary_fallback(other, op);
+    }
+
+    pub fn to_vec(&self) -> Vec<u16> {
+        let mut ret = Vec::with_capacity(self.len);
+        self.visit(|value| ret.push(value));
         ret
     }
+
+    /// Sorts and deduplicates `values` by inserting them in a scratch bitmap
+    /// and decoding it back. It runs in `O(n + 1024)`, which is faster than
+    /// [`slice::sort_unstable`] followed by [`Vec::dedup`] on large inputs.
+    pub fn sort_dedup(values: &mut Vec<u16>) {
+        let mut bitmap = Bitmap::new();
+        for value in values.iter() {
+            bitmap.insert_unchecked(*value);
+        }
+        values.clear();
+        bitmap.visit(|value| values.push(value));
+    }
 }
 
 impl FromIterator<u16> for Bitmap {
@@ -181,16 +667,20 @@ impl PartialEq for Bitmap {
     }
 }
 
+impl Eq for Bitmap {}
+
+/// The length is derived from the store, thus only the store is hashed.
+impl core::hash::Hash for Bitmap {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.store.hash(state);
+    }
+}
+
 impl std::ops::BitOr<&Bitmap> for Bitmap {
     type Output = Bitmap;
 
     fn bitor(mut self, rhs: &Self) -> Self::Output {
-        let mut count = 0;
-        for index in 0..self.store.len() {
-            self.store[index] |= rhs.store[index];
-            count += self.store[index].count_ones();
-        }
-        self.len = count as usize;
+        self.union(rhs);
         self
     }
 }
@@ -220,6 +710,75 @@ impl std::ops::BitAnd for Bitmap {
     }
 }
 
+impl std::ops::BitXor<&Bitmap> for Bitmap {
+    type Output = Bitmap;
+
+    fn bitxor(mut self, rhs: &Self) -> Self::Output {
+        self.symmetric_difference(rhs);
+        self
+    }
+}
+
+impl std::ops::BitXor for Bitmap {
+    type Output = Bitmap;
+
+    fn bitxor(self, rhs: Self) -> Self::Output {
+        self ^ &rhs
+    }
+}
+
+/// `a - &b` is the set difference, the values of `a` absent from `b`.
+impl std::ops::Sub<&Bitmap> for Bitmap {
+    type Output = Bitmap;
+
+    fn sub(mut self, rhs: &Self) -> Self::Output {
+        self.difference(rhs);
+        self
+    }
+}
+
+impl std::ops::Sub for Bitmap {
+    type Output = Bitmap;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        self - &rhs
+    }
+}
+
+/// The operators between two borrowed bitmaps return a new bitmap, the left
+/// operand is copied before the operation is applied in place.
+impl std::ops::BitOr<&Bitmap> for &Bitmap {
+    type Output = Bitmap;
+
+    fn bitor(self, rhs: &Bitmap) -> Self::Output {
+        self.clone() | rhs
+    }
+}
+
+impl std::ops::BitAnd<&Bitmap> for &Bitmap {
+    type Output = Bitmap;
+
+    fn bitand(self, rhs: &Bitmap) -> Self::Output {
+        self.clone() & rhs
+    }
+}
+
+impl std::ops::BitXor<&Bitmap> for &Bitmap {
+    type Output = Bitmap;
+
+    fn bitxor(self, rhs: &Bitmap) -> Self::Output {
+        self.clone() ^ rhs
+    }
+}
+
+impl std::ops::Sub<&Bitmap> for &Bitmap {
+    type Output = Bitmap;
+
+    fn sub(self, rhs: &Bitmap) -> Self::Output {
+        self.clone() - rhs
+    }
+}
+
 impl Default for Bitmap {
     #[inline]
     fn default() -> Self {
@@ -227,9 +786,27 @@ impl Default for Bitmap {
     }
 }
 
+/// Print the values of the bitmap. Large bitmaps are truncated to their first
+/// [`Bitmap::DEBUG_LIMIT`] values, the limit can be changed with the precision:
+/// `format!("{bitmap:.10?}")` prints at most 10 values.
 impl fmt::Debug for Bitmap {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.debug_set().entries(&self.to_vec()).finish()
+        struct More(usize);
+
+        impl fmt::Debug for More {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "… (+{} more)", self.0)
+            }
+        }
+
+        let limit = f.precision().unwrap_or(Self::DEBUG_LIMIT);
+        let values = self.to_vec();
+        let mut set = f.debug_set();
+        set.entries(values.iter().take(limit));
+        if values.len() > limit {
+            set.entry(&More(values.len() - limit));
+        }
+        set.finish()
     }
 }
 
@@ -303,6 +880,103 @@ mod test {
         assert_eq!(orig, other);
     }
 
+    #[test]
+    fn invariants() {
+        let mut bitmap = Bitmap::from_iter([0, 12, 64, u16::MAX]);
+        bitmap.assert_invariants();
+        bitmap.intersection(&Bitmap::full());
+        bitmap.assert_invariants();
+        Bitmap::full().assert_invariants();
+    }
+
+    #[test]
+    #[should_panic = "the bitmap length (3) doesn't match the number of values in the store (2)"]
+    fn broken_invariants() {
+        let mut bitmap = Bitmap::from_iter([0, 12]);
+        bitmap.len = 3;
+        bitmap.assert_invariants();
+    }
+
+    #[test]
+    fn insert_unchecked() {
+        let mut bitmap = Bitmap::new();
+        for value in [3, 3, 64, 90, u16::MAX] {
+            bitmap.insert_unchecked(value);
+        }
+        assert_eq!(bitmap.len(), 0);
+        bitmap.recompute_len();
+        bitmap.assert_invariants();
+
+        insta::assert_debug_snapshot!(bitmap.len(), @"4");
+        insta::assert_debug_snapshot!(bitmap, @r###"
+        {
+            3,
+            64,
+            90,
+            65535,
+        }
+        "###);
+    }
+
+    #[test]
+    fn truncated_debug() {
+        let bitmap = Bitmap::full();
+        insta::assert_snapshot!(format!("{bitmap:.3?}"), @"{0, 1, 2, … (+65533 more)}");
+        insta::assert_snapshot!(format!("{:.0?}", Bitmap::from_iter([1, 2])), @"{… (+2 more)}");
+        insta::assert_snapshot!(format!("{:.2?}", Bitmap::from_iter([1, 2])), @"{1, 2}");
+
+        let debug = format!("{bitmap:?}");
+        assert_eq!(debug.matches(", ").count(), Bitmap::DEBUG_LIMIT);
+        assert!(debug.ends_with(", … (+65436 more)}"), "{debug}");
+    }
+
+    #[test]
+    fn fold_words() {
+        let left = Bitmap::from_iter([0, 1, 64, u16::MAX]);
+        let right = Bitmap::from_iter([1, 2, 64]);
+        let words = left.fold_words(0, |acc, word| acc + (word != 0) as usize);
+        insta::assert_debug_snapshot!(words, @"3");
+        let common = left.fold_words_with(&right, 0, |acc, l, r| acc + (l & r).count_ones());
+        insta::assert_debug_snapshot!(common, @"2");
+    }
+
+    #[test]
+    fn combine_with() {
+        let mut bitmap = Bitmap::from_iter([0, 1, 64, u16::MAX]);
+        let mask = Bitmap::from_iter([1, 2, 64]);
+        bitmap.combine_with(&mask, |l, r| l ^ r);
+        assert_eq!(bitmap.to_vec(), [0, 2, u16::MAX]);
+        bitmap.assert_invariants();
+        bitmap.combine_with(&mask, |_, _| Word::MAX);
+        assert_eq!(bitmap, Bitmap::full());
+    }
+
+    #[test]
+    fn first_absent() {
+        insta::assert_debug_snapshot!(Bitmap::new().first_absent(), @r###"
+        Some(
+            0,
+        )
+        "###);
+        assert_eq!(Bitmap::from_iter(0..100).first_absent(), Some(100));
+        assert_eq!(
+            Bitmap::from_iter((0..64).chain([65])).first_absent(),
+            Some(64)
+        );
+        assert_eq!(
+            Bitmap::from_iter(0..u16::MAX).first_absent(),
+            Some(u16::MAX)
+        );
+        assert_eq!(Bitmap::full().first_absent(), None);
+
+        let bitmap = Bitmap::from_iter((0..100).chain(101..200));
+        assert_eq!(bitmap.next_absent(0), Some(100));
+        assert_eq!(bitmap.next_absent(100), Some(100));
+        assert_eq!(bitmap.next_absent(101), Some(200));
+        assert_eq!(bitmap.next_absent(250), Some(250));
+        assert_eq!(Bitmap::full().next_absent(u16::MAX), None);
+    }
+
     #[test]
     fn contains() {
         let mut bitmap = Bitmap::new();
@@ -377,6 +1051,175 @@ mod test {
         assert_eq!(ret.store, simd.store);
     }
 
+    #[test]
+    fn append() {
+        let mut left = Bitmap::from_iter([0, 1, 2]);
+        let mut right = Bitmap::from_iter([2, 3, u16::MAX]);
+        left.append(&mut right);
+
+        assert!(right.is_empty());
+        assert_eq!(right, Bitmap::new());
+        insta::assert_debug_snapshot!(left.len(), @"5");
+        insta::assert_debug_snapshot!(left, @r###"
+        {
+            0,
+            1,
+            2,
+            3,
+            65535,
+        }
+        "###);
+    }
+
+    #[test]
+    fn ternary() {
+        let a = Bitmap::from_iter(0..10);
+        let b = Bitmap::from_iter((0..10).step_by(2));
+        let c = Bitmap::from_iter([100, 101]);
+
+        let mut and_or = a.clone();
+        and_or.and_or(&b, &c);
+        insta::assert_debug_snapshot!(and_or, @r###"
+        {
+            0,
+            2,
+            4,
+            6,
+            8,
+            100,
+            101,
+        }
+        "###);
+
+        let mut and_not_or = a.clone();
+        and_not_or.and_not_or(&b, &c);
+        insta::assert_debug_snapshot!(and_not_or, @r###"
+        {
+            1,
+            3,
+            5,
+            7,
+            9,
+            100,
+            101,
+        }
+        "###);
+
+        let mut or_and = b.clone();
+        or_and.or_and(&c, &Bitmap::from_iter([1, 2, 3, 100]));
+        assert_eq!(or_and.to_vec(), [2, 100]);
+    }
+
+    #[test]
+    fn intersection_many() {
+        let a = Bitmap::from_iter([0, 1, 63, 64, u16::MAX]);
+        let b = Bitmap::from_iter([1, 63, 64, u16::MAX]);
+        let c = Bitmap::from_iter([0, 1, 64, u16::MAX]);
+        assert_eq!(
+            Bitmap::intersection_many(&[&a, &b, &c]).to_vec(),
+            [1, 64, u16::MAX]
+        );
+        assert_eq!(Bitmap::intersection_many(&[&a]), a);
+        assert!(Bitmap::intersection_many(&[&a, &Bitmap::new()]).is_empty());
+        assert!(Bitmap::intersection_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn union_many() {
+        let a = Bitmap::from_iter([0, 63]);
+        let b = Bitmap::from_iter([1, 63, u16::MAX]);
+        let c = Bitmap::from_iter([64, 5000]);
+        assert_eq!(
+            Bitmap::union_many(&[&a, &b, &c]).to_vec(),
+            [0, 1, 63, 64, 5000, u16::MAX]
+        );
+        assert_eq!(Bitmap::union_many(&[&a]), a);
+        assert!(Bitmap::union_many(&[]).is_empty());
+    }
+
+    #[test]
+    fn intersection_with_slice() {
+        let mut bitmap = Bitmap::from_iter([0, 1, 63, 64, 5000, u16::MAX]);
+        let sorted = [1, 1, 2, 64, 4000, u16::MAX];
+        assert_eq!(bitmap.intersection_len_with_slice(&sorted), 3);
+        bitmap.intersection_with_slice(&sorted);
+        assert_eq!(bitmap.to_vec(), [1, 64, u16::MAX]);
+        bitmap.intersection_with_slice(&[]);
+        assert!(bitmap.is_empty());
+    }
+
+    #[test]
+    fn intersection3_len() {
+        let a = Bitmap::from_iter(0..10);
+        let b = Bitmap::from_iter((0..10).step_by(2));
+        let c = Bitmap::from_iter([100, 101]);
+
+        insta::assert_debug_snapshot!(Bitmap::intersection3_len(&a, &b, &c), @"0");
+        insta::assert_debug_snapshot!(Bitmap::intersection3_len(&a, &b, &a), @"5");
+        insta::assert_debug_snapshot!(Bitmap::intersection3_len(&c, &c, &c), @"2");
+    }
+
+    #[test]
+    fn intersection_len_many() {
+        let query = Bitmap::from_iter((0..100).chain([u16::MAX]));
+        let a = Bitmap::from_iter((0..10).step_by(2));
+        let b = Bitmap::from_iter([99, 100, u16::MAX]);
+        assert_eq!(
+            query.intersection_len_many(&[&a, &b, &Bitmap::full(), &Bitmap::new()]),
+            [5, 2, 101, 0]
+        );
+        assert!(query.intersection_len_many(&[]).is_empty());
+        assert_eq!(Bitmap::new().intersection_len_many(&[&a]), [0]);
+    }
+
+    #[test]
+    fn from_words() {
+        static BITMAP: Bitmap = Bitmap::from_words([0b101; Bitmap::BITMAP_SIZE]);
+        assert_eq!(BITMAP.len(), 2 * Bitmap::BITMAP_SIZE);
+        assert_eq!(
+            Bitmap::from_words(*Bitmap::full().internal_store()),
+            Bitmap::full()
+        );
+    }
+
+    #[cfg(feature = "macros")]
+    #[test]
+    fn include_bitmap() {
+        static IDS: Bitmap = crate::include_bitmap!("macros/fixtures/ids.txt");
+        assert_eq!(IDS.to_vec(), [0, 3, 4, 5, 64, 1000, 1001, u16::MAX]);
+    }
+
+    #[test]
+    fn sort_dedup() {
+        let mut values = vec![u16::MAX, 3, 1, 3, 0, 64, 1];
+        Bitmap::sort_dedup(&mut values);
+        assert_eq!(values, [0, 1, 3, 64, u16::MAX]);
+
+        let mut values = Vec::new();
+        Bitmap::sort_dedup(&mut values);
+        assert!(values.is_empty());
+    }
+
+    #[test]
+    fn estimate_intersection_len() {
+        let left = Bitmap::from_iter((0..=u16::MAX).step_by(2));
+        let right = Bitmap::from_iter((0..=u16::MAX).step_by(3));
+        insta::assert_debug_snapshot!(left.estimate_intersection_len(&right, 16), @"10944");
+        assert_eq!(
+            left.estimate_intersection_len(&right, 1024),
+            left.intersection_iter(&right).count()
+        );
+        assert_eq!(
+            left.estimate_intersection_len(&right, 5000),
+            left.intersection_iter(&right).count()
+        );
+        assert_eq!(left.estimate_intersection_len(&Bitmap::new(), 0), 0);
+        assert_eq!(
+            Bitmap::full().estimate_intersection_len(&Bitmap::full(), 3),
+            65536
+        );
+    }
+
     #[test]
     fn or() {
         let left = Bitmap::from_iter((0..10).step_by(2).chain(10..15));
@@ -405,6 +1248,68 @@ mod test {
         "###);
     }
 
+    #[test]
+    fn union() {
+        let mut bitmap = Bitmap::from_iter([0, 63, u16::MAX]);
+        bitmap.union(&Bitmap::from_iter([1, 63, 64]));
+        assert_eq!(bitmap.to_vec(), [0, 1, 63, 64, u16::MAX]);
+        assert_eq!(bitmap.len(), 5);
+        bitmap.union(&Bitmap::full());
+        assert_eq!(bitmap, Bitmap::full());
+    }
+
+    #[test]
+    fn difference() {
+        let mut bitmap = Bitmap::from_iter([0, 63, 64, u16::MAX]);
+        bitmap.difference(&Bitmap::from_iter([1, 63, u16::MAX]));
+        assert_eq!(bitmap.to_vec(), [0, 64]);
+        assert_eq!(bitmap.len(), 2);
+        bitmap.difference(&Bitmap::full());
+        assert!(bitmap.is_empty());
+    }
+
+    #[test]
+    fn symmetric_difference() {
+        let mut bitmap = Bitmap::from_iter([0, 63, 64, u16::MAX]);
+        bitmap.symmetric_difference(&Bitmap::from_iter([1, 63, u16::MAX]));
+        assert_eq!(bitmap.to_vec(), [0, 1, 64]);
+        assert_eq!(bitmap.len(), 3);
+        bitmap.symmetric_difference(&Bitmap::full());
+        assert_eq!(bitmap.len(), u16::MAX as usize + 1 - 3);
+        assert!(!bitmap.contains(64));
+    }
+
+    #[test]
+    fn xor() {
+        let left = Bitmap::from_iter([0, 1, 63, u16::MAX]);
+        let right = Bitmap::from_iter([1, 64, u16::MAX]);
+        assert_eq!((left.clone() ^ &right).to_vec(), [0, 63, 64]);
+        assert_eq!((left.clone() ^ right), Bitmap::from_iter([0, 63, 64]));
+        assert!((left.clone() ^ left).is_empty());
+    }
+
+    #[test]
+    fn sub() {
+        let left = Bitmap::from_iter([0, 1, 63, u16::MAX]);
+        let right = Bitmap::from_iter([1, 64, u16::MAX]);
+        assert_eq!((left.clone() - &right).to_vec(), [0, 63]);
+        assert_eq!((right - left.clone()).to_vec(), [64]);
+        assert!((left - Bitmap::full()).is_empty());
+    }
+
+    #[test]
+    fn borrowed_operators() {
+        let left = Bitmap::from_iter([0, 1, 63, u16::MAX]);
+        let right = Bitmap::from_iter([1, 64, u16::MAX]);
+        assert_eq!((&left & &right).to_vec(), [1, u16::MAX]);
+        assert_eq!((&left | &right).to_vec(), [0, 1, 63, 64, u16::MAX]);
+        assert_eq!((&left ^ &right).to_vec(), [0, 63, 64]);
+        assert_eq!((&left - &right).to_vec(), [0, 63]);
+        // both operands are still usable
+        assert_eq!(left.len(), 4);
+        assert_eq!(right.len(), 3);
+    }
+
     proptest! {
         #[test]
         fn from_iter_and_insert_are_equivalent(indexes in prop::collection::vec(0..=u16::MAX, 1..150)) {
@@ -442,6 +1347,89 @@ mod test {
             assert_eq!(classic, simd, "\nclassic:\n{classic:?}\nsimd:\n{simd:?}");
         }
 
+        #[test]
+        fn prop_and_shapes(left in strategies::any(), right in strategies::any()) {
+            let bitmap = left.clone() & &right;
+            let expected: Vec<u16> = left.to_vec().into_iter().filter(|v| right.contains(*v)).collect();
+            assert_eq!(bitmap.to_vec(), expected);
+        }
+
+        #[test]
+        fn prop_ternary(a in strategies::any(), b in strategies::any(), c in strategies::any()) {
+            let mut and_or = a.clone();
+            and_or.and_or(&b, &c);
+            assert_eq!(and_or, (a.clone() & &b) | &c);
+
+            let mut or_and = a.clone();
+            or_and.or_and(&b, &c);
+            assert_eq!(or_and, (&a | &b) & &c);
+
+            let not_b = Bitmap::from_iter((0..=u16::MAX).filter(|v| !b.contains(*v)));
+            let mut and_not_or = a.clone();
+            and_not_or.and_not_or(&b, &c);
+            assert_eq!(and_not_or, (a & not_b) | c);
+        }
+
+        #[test]
+        fn prop_intersection_many(bitmaps in prop::collection::vec(strategies::any(), 1..6)) {
+            let mut expected = bitmaps[0].clone();
+            for bitmap in &bitmaps[1..] {
+                expected.intersection(bitmap);
+            }
+            let bitmaps: Vec<&Bitmap> = bitmaps.iter().collect();
+            assert_eq!(Bitmap::intersection_many(&bitmaps), expected);
+        }
+
+        #[test]
+        fn prop_union_many(bitmaps in prop::collection::vec(strategies::any(), 0..6)) {
+            let expected = Bitmap::union_iter_many(&bitmaps).collect::<Bitmap>();
+            let bitmaps: Vec<&Bitmap> = bitmaps.iter().collect();
+            let union = Bitmap::union_many(&bitmaps);
+            union.assert_invariants();
+            assert_eq!(union, expected);
+        }
+
+        #[test]
+        fn prop_intersection_with_slice(bitmap in strategies::any(), mut values in prop::collection::vec(any::<u16>(), 0..300)) {
+            values.sort_unstable();
+            let expected = bitmap.clone() & &Bitmap::from_iter(&values);
+            assert_eq!(bitmap.intersection_len_with_slice(&values), expected.len());
+            let mut bitmap = bitmap;
+            bitmap.intersection_with_slice(&values);
+            assert_eq!(bitmap, expected);
+        }
+
+        #[test]
+        fn prop_intersection3_len(a in strategies::any(), b in strategies::any(), c in strategies::any()) {
+            let expected = (a.clone() & &b) & &c;
+            assert_eq!(Bitmap::intersection3_len(&a, &b, &c), expected.len());
+        }
+
+        #[test]
+        fn prop_intersection_len_many(query in strategies::any(), others in prop::collection::vec(strategies::any(), 0..5)) {
+            let expected: Vec<usize> = others.iter().map(|other| (query.clone() & other).len()).collect();
+            let others: Vec<&Bitmap> = others.iter().collect();
+            assert_eq!(query.intersection_len_many(&others), expected);
+        }
+
+        #[test]
+        fn prop_sort_dedup(mut values in prop::collection::vec(any::<u16>(), 0..1000)) {
+            let mut expected = values.clone();
+            expected.sort_unstable();
+            expected.dedup();
+            Bitmap::sort_dedup(&mut values);
+            assert_eq!(values, expected);
+        }
+
+        #[test]
+        fn prop_estimate_intersection_len(a in strategies::any(), b in strategies::any(), sample_words in 0..2000_usize) {
+            let estimate = a.estimate_intersection_len(&b, sample_words);
+            assert!(estimate <= u16::MAX as usize + 1);
+            if sample_words >= Bitmap::BITMAP_SIZE {
+                assert_eq!(estimate, Bitmap::intersection3_len(&a, &b, &b));
+            }
+        }
+
         #[test]
         fn prop_or(left in prop::collection::vec(0..=u16::MAX, 1..150), right in prop::collection::vec(0..=u16::MAX, 1..150)) {
             let bleft = Bitmap::from_iter(&left);
@@ -456,6 +1444,59 @@ mod test {
             assert_eq!(bitmap.to_vec(), hashset);
         }
 
+        #[test]
+        fn prop_difference(left in strategies::any(), right in strategies::any()) {
+            let mut bitmap = left.clone();
+            bitmap.difference(&right);
+            assert!(bitmap.iter().eq(left.difference_iter(&right)));
+            bitmap.assert_invariants();
+        }
+
+        #[test]
+        fn prop_simd_difference(left in strategies::any(), right in strategies::any()) {
+            let mut scalar = left.clone();
+            scalar.difference(&right);
+            let mut simd = left.clone();
+            simd.difference_simd(&right);
+            assert_eq!(scalar, simd);
+            simd.assert_invariants();
+        }
+
+        #[test]
+        fn prop_symmetric_difference(left in strategies::any(), right in strategies::any()) {
+            let mut bitmap = left.clone();
+            bitmap.symmetric_difference(&right);
+            assert!(bitmap.iter().eq(left.symmetric_difference_iter(&right)));
+            bitmap.assert_invariants();
+        }
+
+        #[test]
+        fn prop_simd_symmetric_difference(left in strategies::any(), right in strategies::any()) {
+            let mut scalar = left.clone();
+            scalar.symmetric_difference(&right);
+            let mut simd = left.clone();
+            simd.symmetric_difference_simd(&right);
+            assert_eq!(scalar, simd);
+            simd.assert_invariants();
+        }
+
+        #[test]
+        fn prop_simd_or(left in strategies::any(), right in strategies::any()) {
+            let mut scalar = left.clone();
+            scalar.union(&right);
+            let mut simd = left.clone();
+            simd.union_simd(&right);
+            assert_eq!(scalar, simd);
+            simd.assert_invariants();
+        }
+
+        #[test]
+        fn prop_union(left in strategies::any(), right in strategies::any()) {
+            let mut bitmap = left.clone();
+            bitmap.union(&right);
+            assert!(bitmap.iter().eq(left.union_iter(&right)));
+            bitmap.assert_invariants();
+        }
     }
 
     // These tests are too slow to be ran multiple times. But even by executing them only once, if there is a bug they'll end up by find it over time.
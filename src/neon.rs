@@ -0,0 +1,123 @@
+//! The kernels vectorized with the NEON instructions of aarch64.
+//!
+//! The kernels never walk raw pointers: the driver hands out whole [`Chunk`]s
+//! of words, and the intrinsics are only called through the safe methods of
+//! [`Lane`], which load and store these chunks.
+
+use core::arch::aarch64::*;
+
+use crate::backend::Backend;
+use crate::Word;
+
+/// Number of words held by a NEON register.
+const LANE_WORDS: usize = 2;
+
+/// The words loaded in or stored from a single register.
+type Chunk = [Word; LANE_WORDS];
+
+/// A NEON register holding a [`Chunk`] of words.
+#[derive(Clone, Copy)]
+pub(crate) struct Lane(uint64x2_t);
+
+impl Lane {
+    #[inline]
+    fn load(chunk: &Chunk) -> Self {
+        // SAFETY: the reference is valid for reads of two `u64` and properly aligned,
+        // and NEON is statically enabled since this module is only compiled with it.
+        Lane(unsafe { vld1q_u64(chunk.as_ptr()) })
+    }
+
+    #[inline]
+    fn store(self, chunk: &mut Chunk) {
+        // SAFETY: the reference is valid for writes of two `u64` and properly aligned.
+        unsafe { vst1q_u64(chunk.as_mut_ptr(), self.0) }
+    }
+
+    #[inline]
+    fn and(self, other: Self) -> Self {
+        // SAFETY: NEON is available, the intrinsic only works on registers.
+        Lane(unsafe { vandq_u64(self.0, other.0) })
+    }
+
+    #[inline]
+    fn or(self, other: Self) -> Self {
+        // SAFETY: NEON is available, the intrinsic only works on registers.
+        Lane(unsafe { vorrq_u64(self.0, other.0) })
+    }
+
+    /// Returns `self & !other`.
+    #[inline]
+    fn and_not(self, other: Self) -> Self {
+        // SAFETY: NEON is available, the intrinsic only works on registers.
+        Lane(unsafe { vbicq_u64(self.0, other.0) })
+    }
+
+    #[inline]
+    fn xor(self, other: Self) -> Self {
+        // SAFETY: NEON is available, the intrinsic only works on registers.
+        Lane(unsafe { veorq_u64(self.0, other.0) })
+    }
+
+    #[inline]
+    fn not(self) -> Self {
+        // SAFETY: NEON is available, the intrinsics only work on registers.
+        // There is no 64 bits variant of the NOT, the bits are flipped all the same.
+        Lane(unsafe { vreinterpretq_u64_u32(vmvnq_u32(vreinterpretq_u32_u64(self.0))) })
+    }
+
+    #[inline]
+    fn count_ones(self) -> usize {
+        // SAFETY: NEON is available, the intrinsics only work on registers.
+        unsafe { vaddvq_u8(vcntq_u8(vreinterpretq_u8_u64(self.0))) as usize }
+    }
+}
+
+/// The backend of the aarch64 targets with NEON enabled.
+#[derive(Clone, Copy)]
+pub(crate) struct Neon;
+
+impl Backend for Neon {
+    type Lane = Lane;
+
+    const LANE_WORDS: usize = LANE_WORDS;
+
+    #[inline(always)]
+    fn load(self, words: &[Word]) -> Lane {
+        Lane::load(words.try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn store(self, lane: Lane, words: &mut [Word]) {
+        lane.store(words.try_into().unwrap())
+    }
+
+    #[inline(always)]
+    fn and(self, left: Lane, right: Lane) -> Lane {
+        left.and(right)
+    }
+
+    #[inline(always)]
+    fn or(self, left: Lane, right: Lane) -> Lane {
+        left.or(right)
+    }
+
+    #[inline(always)]
+    fn and_not(self, left: Lane, right: Lane) -> Lane {
+        left.and_not(right)
+    }
+
+    #[inline(always)]
+    fn xor(self, left: Lane, right: Lane) -> Lane {
+        left.xor(right)
+    }
+
+    #[inline(always)]
+    fn not(self, lane: Lane) -> Lane {
+        lane.not()
+    }
+
+    #[inline(always)]
+    fn count_ones(self, lane: Lane) -> usize {
+        lane.count_ones()
+    }
+}
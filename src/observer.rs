@@ -0,0 +1,181 @@
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::{Bitmap, Word};
+
+/// A modification of an [`ObservedBitmap`]. The single values are reported
+/// as ranges of one value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Change {
+    Inserted(RangeInclusive<u16>),
+    Removed(RangeInclusive<u16>),
+}
+
+/// A bitmap calling an observer with every [`Change`] actually applied to it,
+/// so secondary indexes and caches can stay in sync without diffing the
+/// bitmaps after every mutation. Inserting a value already present, or
+/// removing an absent one, doesn't notify the observer.
+///
+/// The observer can forward the changes to a channel:
+/// ```
+/// use std::sync::mpsc;
+/// use bitmap::{Bitmap, Change, ObservedBitmap};
+///
+/// let (sender, receiver) = mpsc::channel();
+/// let mut bitmap = ObservedBitmap::new(Bitmap::new(), move |change| sender.send(change).unwrap());
+/// bitmap.insert(3);
+/// bitmap.union_with(&Bitmap::from_iter(1..=5));
+/// assert_eq!(receiver.try_iter().collect::<Vec<_>>(), [
+///     Change::Inserted(3..=3),
+///     Change::Inserted(1..=2),
+///     Change::Inserted(4..=5),
+/// ]);
+/// ```
+pub struct ObservedBitmap<F: FnMut(Change)> {
+    bitmap: Bitmap,
+    observer: F,
+}
+
+impl<F: FnMut(Change)> ObservedBitmap<F> {
+    /// The values already in `bitmap` aren't reported to the observer.
+    pub fn new(bitmap: Bitmap, observer: F) -> Self {
+        ObservedBitmap { bitmap, observer }
+    }
+
+    #[inline]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    pub fn into_bitmap(self) -> Bitmap {
+        self.bitmap
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        self.bitmap.contains(value)
+    }
+
+    /// Insert a value in the bitmap.
+    /// Returns `true` if the value was not already present in the bitmap.
+    #[inline]
+    pub fn insert(&mut self, value: u16) -> bool {
+        let inserted = self.bitmap.insert(value);
+        if inserted {
+            (self.observer)(Change::Inserted(value..=value));
+        }
+        inserted
+    }
+
+    /// Remove a value from the bitmap.
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn remove(&mut self, value: u16) -> bool {
+        let removed = self.bitmap.remove(value);
+        if removed {
+            (self.observer)(Change::Removed(value..=value));
+        }
+        removed
+    }
+
+    /// Calls the observer with the runs of values set in the words returned
+    /// by `diff` for every pair of words of `self` and `other`.
+    fn notify(
+        &mut self,
+        other: &Bitmap,
+        diff: impl Fn(Word, Word) -> Word,
+        change: fn(RangeInclusive<u16>) -> Change,
+    ) {
+        let mut changed = Bitmap::new();
+        for (key, word) in changed.store.iter_mut().enumerate() {
+            *word = diff(self.bitmap.store[key], other.store[key]);
+        }
+        changed.recompute_len();
+        changed.ranges().map(change).for_each(&mut self.observer);
+    }
+
+    /// Insert all the values of `other`, the observer receives the runs of
+    /// values that were missing.
+    pub fn union_with(&mut self, other: &Bitmap) {
+        self.notify(other, |left, right| right & !left, Change::Inserted);
+        self.bitmap = core::mem::take(&mut self.bitmap) | other;
+    }
+
+    /// Remove all the values absent from `other`, the observer receives the
+    /// runs of values that were removed.
+    pub fn intersect_with(&mut self, other: &Bitmap) {
+        self.notify(other, |left, right| left & !right, Change::Removed);
+        self.bitmap.intersection(other);
+    }
+
+    /// Remove all the values, the observer receives all the runs of values.
+    pub fn clear(&mut self) {
+        let bitmap = core::mem::take(&mut self.bitmap);
+        bitmap
+            .ranges()
+            .map(Change::Removed)
+            .for_each(&mut self.observer);
+    }
+}
+
+impl<F: FnMut(Change)> fmt::Debug for ObservedBitmap<F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.bitmap.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn observed() {
+        let mut changes = Vec::new();
+        let mut bitmap =
+            ObservedBitmap::new(Bitmap::from_iter([10]), |change| changes.push(change));
+        assert!(bitmap.insert(1));
+        assert!(!bitmap.insert(1));
+        assert!(!bitmap.remove(2));
+        assert!(bitmap.remove(10));
+        bitmap.union_with(&Bitmap::from_iter((0..5).chain(63..=64)));
+        bitmap.intersect_with(&Bitmap::from_iter([0, 4, 64]));
+        assert_eq!(bitmap.bitmap().to_vec(), [0, 4, 64]);
+        bitmap.clear();
+        assert!(bitmap.is_empty());
+        insta::assert_snapshot!(format!("{changes:?}"), @"[Inserted(1..=1), Removed(10..=10), Inserted(0..=0), Inserted(2..=4), Inserted(63..=64), Removed(1..=3), Removed(63..=63), Removed(0..=0), Removed(4..=4), Removed(64..=64)]");
+    }
+
+    proptest! {
+        #[test]
+        fn prop_replay(initial in crate::strategies::any(), union in crate::strategies::any(), inter in crate::strategies::any(), ops in prop::collection::vec((any::<bool>(), any::<u16>()), 0..50)) {
+            let mut replica = initial.clone();
+            let mut bitmap = ObservedBitmap::new(initial, |change| match change {
+                Change::Inserted(range) => range.for_each(|value| assert!(replica.insert(value))),
+                Change::Removed(range) => range.for_each(|value| assert!(replica.remove(value))),
+            });
+            bitmap.union_with(&union);
+            for (insert, value) in ops {
+                if insert {
+                    bitmap.insert(value);
+                } else {
+                    bitmap.remove(value);
+                }
+            }
+            bitmap.intersect_with(&inter);
+            let bitmap = bitmap.into_bitmap();
+            assert_eq!(replica, bitmap);
+        }
+    }
+}
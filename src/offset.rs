@@ -0,0 +1,229 @@
+use core::fmt;
+use core::ops::RangeInclusive;
+
+use crate::Bitmap;
+
+/// A bitmap over a window of 65536 consecutive `u32` starting at `base`,
+/// like `100_000..=165_535`. The value `v` is stored as `v - base` in a [`Bitmap`].
+#[derive(Clone, PartialEq, Default)]
+pub struct OffsetBitmap {
+    base: u32,
+    bitmap: Bitmap,
+}
+
+impl OffsetBitmap {
+    /// Creates an empty bitmap over `base..=base + 65535`.
+    ///
+    /// # Panics
+    ///
+    /// If the window doesn't fit in a `u32`.
+    pub fn new(base: u32) -> Self {
+        Self::from_bitmap(base, Bitmap::new())
+    }
+
+    /// Creates a bitmap over `base..=base + 65535` containing `base + v` for
+    /// every value `v` of `bitmap`.
+    ///
+    /// # Panics
+    ///
+    /// If the window doesn't fit in a `u32`.
+    pub fn from_bitmap(base: u32, bitmap: Bitmap) -> Self {
+        assert!(
+            base.checked_add(u16::MAX as u32).is_some(),
+            "the window starting at {base} overflows a u32"
+        );
+        OffsetBitmap { base, bitmap }
+    }
+
+    #[inline]
+    pub fn base(&self) -> u32 {
+        self.base
+    }
+
+    /// The values relative to the base.
+    #[inline]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    pub fn into_bitmap(self) -> Bitmap {
+        self.bitmap
+    }
+
+    /// The values that can be stored in the bitmap.
+    #[inline]
+    pub fn universe(&self) -> RangeInclusive<u32> {
+        self.base..=self.base + u16::MAX as u32
+    }
+
+    #[inline]
+    fn offset(&self, value: u32) -> Option<u16> {
+        u16::try_from(value.checked_sub(self.base)?).ok()
+    }
+
+    /// Insert a value in the bitmap.
+    /// Returns `None` if the value is outside of the [universe](Self::universe),
+    /// and otherwise `true` if the value was not already present in the bitmap.
+    #[inline]
+    pub fn insert(&mut self, value: u32) -> Option<bool> {
+        let offset = self.offset(value)?;
+        Some(self.bitmap.insert(offset))
+    }
+
+    /// Remove a value from the bitmap.
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn remove(&mut self, value: u32) -> bool {
+        self.offset(value)
+            .is_some_and(|offset| self.bitmap.remove(offset))
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u32) -> bool {
+        self.offset(value)
+            .is_some_and(|offset| self.bitmap.contains(offset))
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    /// Iterates over the values of the bitmap in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        self.bitmap.iter().map(|offset| self.base + offset as u32)
+    }
+
+    /// Moves the bitmap to the window starting at `base`. The values outside
+    /// of the new window are dropped.
+    ///
+    /// # Panics
+    ///
+    /// If the new window doesn't fit in a `u32`.
+    pub fn rebase(&self, base: u32) -> Self {
+        let mut rebased = OffsetBitmap::new(base);
+        if base == self.base {
+            rebased.bitmap = self.bitmap.clone();
+            return rebased;
+        }
+        self.bitmap.visit(|offset| {
+            rebased.insert(self.base + offset as u32);
+        });
+        rebased
+    }
+
+    #[track_caller]
+    fn assert_same_base(&self, other: &Self) {
+        assert_eq!(
+            self.base, other.base,
+            "the bitmaps have different bases, rebase one of them first"
+        );
+    }
+
+    /// Computes the intersection of both bitmaps in place.
+    ///
+    /// # Panics
+    ///
+    /// If the bitmaps don't share the same base, see [`OffsetBitmap::rebase`].
+    pub fn intersection(&mut self, other: &Self) {
+        self.assert_same_base(other);
+        self.bitmap.intersection(&other.bitmap);
+    }
+
+    /// Computes the union of both bitmaps in place.
+    ///
+    /// # Panics
+    ///
+    /// If the bitmaps don't share the same base, see [`OffsetBitmap::rebase`].
+    pub fn union(&mut self, other: &Self) {
+        self.assert_same_base(other);
+        self.bitmap = core::mem::take(&mut self.bitmap) | &other.bitmap;
+    }
+}
+
+impl fmt::Debug for OffsetBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn offset() {
+        let mut bitmap = OffsetBitmap::new(100_000);
+        assert_eq!(bitmap.universe(), 100_000..=165_535);
+        assert_eq!(bitmap.insert(100_000), Some(true));
+        assert_eq!(bitmap.insert(100_000), Some(false));
+        assert_eq!(bitmap.insert(165_535), Some(true));
+        assert_eq!(bitmap.insert(99_999), None);
+        assert_eq!(bitmap.insert(165_536), None);
+        assert!(bitmap.contains(165_535));
+        assert!(!bitmap.contains(99_999));
+        assert!(!bitmap.remove(3));
+        assert_eq!(bitmap.len(), 2);
+        insta::assert_debug_snapshot!(bitmap, @r###"
+        {
+            100000,
+            165535,
+        }
+        "###);
+        assert_eq!(bitmap.bitmap().to_vec(), [0, u16::MAX]);
+
+        let rebased = bitmap.rebase(100_010);
+        assert_eq!(rebased.iter().collect::<Vec<_>>(), [165_535]);
+        assert_eq!(bitmap.rebase(100_000), bitmap);
+        assert_eq!(
+            OffsetBitmap::new(u32::MAX - u16::MAX as u32)
+                .universe()
+                .end(),
+            &u32::MAX
+        );
+    }
+
+    #[test]
+    #[should_panic = "the window starting at 4294967295 overflows a u32"]
+    fn overflowing_base() {
+        OffsetBitmap::new(u32::MAX);
+    }
+
+    #[test]
+    #[should_panic = "the bitmaps have different bases, rebase one of them first"]
+    fn different_bases() {
+        OffsetBitmap::new(0).union(&OffsetBitmap::new(1));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_rebase(values in prop::collection::vec(1000..70_000_u32, 0..100), base in 0..2000_u32, new_base in 0..2000_u32) {
+            let mut bitmap = OffsetBitmap::new(base);
+            for value in &values {
+                bitmap.insert(*value);
+            }
+            let rebased = bitmap.rebase(new_base);
+            let expected: Vec<u32> = bitmap.iter().filter(|v| rebased.universe().contains(v)).collect();
+            assert_eq!(rebased.iter().collect::<Vec<_>>(), expected);
+        }
+
+        #[test]
+        fn prop_set_ops(left in crate::strategies::any(), right in crate::strategies::any(), base in any::<u16>()) {
+            let base = base as u32 * 7;
+            let mut inter = OffsetBitmap::from_bitmap(base, left.clone());
+            inter.intersection(&OffsetBitmap::from_bitmap(base, right.clone()));
+            assert_eq!(inter.bitmap(), &(left.clone() & &right));
+
+            let mut union = OffsetBitmap::from_bitmap(base, left.clone());
+            union.union(&OffsetBitmap::from_bitmap(base, right.clone()));
+            assert!(union.iter().eq((left | &right).iter().map(|v| base + v as u32)));
+        }
+    }
+}
@@ -0,0 +1,162 @@
+use crate::{Bitmap, ShardedBitmap};
+
+/// The core operations of a set of `u16`, implemented by the different
+/// representations of this crate so code can be written once for all of them.
+///
+/// ```
+/// use bitmap::{Bitmap, BitmapOps};
+///
+/// fn keep_even<B: BitmapOps>(bitmap: &mut B) {
+///     let odd: Vec<u16> = bitmap.values().filter(|v| v % 2 == 1).collect();
+///     for value in odd {
+///         bitmap.remove(value);
+///     }
+/// }
+///
+/// let mut bitmap = Bitmap::from_iter(0..10);
+/// keep_even(&mut bitmap);
+/// assert_eq!(bitmap.to_vec(), [0, 2, 4, 6, 8]);
+/// ```
+pub trait BitmapOps {
+    /// Returns `true` if the value was not already present in the bitmap.
+    fn insert(&mut self, value: u16) -> bool;
+
+    /// Returns `true` if the value was present in the bitmap.
+    fn remove(&mut self, value: u16) -> bool;
+
+    fn contains(&self, value: u16) -> bool;
+
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert all the values of `other` in `self`.
+    fn union_with(&mut self, other: &Self);
+
+    /// Remove all the values of `self` that are not in `other`.
+    fn intersect_with(&mut self, other: &Self);
+
+    /// Iterates over the values in increasing order.
+    fn values(&self) -> impl Iterator<Item = u16> + '_;
+}
+
+impl BitmapOps for Bitmap {
+    #[inline]
+    fn insert(&mut self, value: u16) -> bool {
+        Bitmap::insert(self, value)
+    }
+
+    #[inline]
+    fn remove(&mut self, value: u16) -> bool {
+        Bitmap::remove(self, value)
+    }
+
+    #[inline]
+    fn contains(&self, value: u16) -> bool {
+        Bitmap::contains(self, value)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        Bitmap::len(self)
+    }
+
+    #[inline]
+    fn union_with(&mut self, other: &Self) {
+        self.union(other);
+    }
+
+    #[inline]
+    fn intersect_with(&mut self, other: &Self) {
+        self.intersection(other);
+    }
+
+    #[inline]
+    fn values(&self) -> impl Iterator<Item = u16> + '_ {
+        self.iter()
+    }
+}
+
+/// The sharded bitmap can be used from a single thread through this trait.
+/// The iteration is done over a snapshot of the bitmap.
+impl BitmapOps for ShardedBitmap {
+    #[inline]
+    fn insert(&mut self, value: u16) -> bool {
+        ShardedBitmap::insert(self, value)
+    }
+
+    #[inline]
+    fn remove(&mut self, value: u16) -> bool {
+        ShardedBitmap::remove(self, value)
+    }
+
+    #[inline]
+    fn contains(&self, value: u16) -> bool {
+        ShardedBitmap::contains(self, value)
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        ShardedBitmap::len(self)
+    }
+
+    fn union_with(&mut self, other: &Self) {
+        self.merge(&other.snapshot());
+    }
+
+    fn intersect_with(&mut self, other: &Self) {
+        let mut snapshot = self.snapshot();
+        snapshot.intersection(&other.snapshot());
+        *self = ShardedBitmap::new(self.shards());
+        self.merge(&snapshot);
+    }
+
+    fn values(&self) -> impl Iterator<Item = u16> + '_ {
+        self.snapshot().to_vec().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn exercise<B: BitmapOps>(new: impl Fn() -> B) -> Vec<u16> {
+        let mut left = new();
+        assert!(left.is_empty());
+        assert!(left.insert(1));
+        assert!(!left.insert(1));
+        left.insert(2);
+        left.insert(3);
+        assert!(left.remove(3));
+        assert!(!left.contains(3));
+
+        let mut right = new();
+        right.insert(2);
+        right.insert(u16::MAX);
+        left.union_with(&right);
+        assert_eq!(left.len(), 3);
+
+        let mut other = new();
+        other.insert(1);
+        other.insert(u16::MAX);
+        left.intersect_with(&other);
+        left.values().collect()
+    }
+
+    fn sharded() -> ShardedBitmap {
+        ShardedBitmap::new(4)
+    }
+
+    #[test]
+    fn generic() {
+        insta::assert_debug_snapshot!(exercise(Bitmap::new), @r###"
+        [
+            1,
+            65535,
+        ]
+        "###);
+        assert_eq!(exercise(Bitmap::new), exercise(sharded));
+    }
+}
@@ -0,0 +1,220 @@
+use core::fmt;
+use std::sync::Arc;
+
+use crate::iter::Values;
+use crate::{Bitmap, Word};
+
+/// Number of words of a chunk, 512 bytes.
+const CHUNK_WORDS: usize = 64;
+/// Number of chunks of a bitmap.
+const CHUNKS: usize = Bitmap::BITMAP_SIZE / CHUNK_WORDS;
+
+type Chunk = Arc<[Word; CHUNK_WORDS]>;
+
+/// A bitmap whose versions share their unmodified parts.
+///
+/// The store is split in 16 chunks of 512 bytes behind an [`Arc`]. Cloning a
+/// `PersistentBitmap` only clones the pointers, and updating a version copies
+/// the chunk containing the value if it's shared with another version. Thus,
+/// keeping many snapshots of a bitmap slowly evolving costs a few chunks per
+/// version instead of 8 KiB.
+#[derive(Clone)]
+pub struct PersistentBitmap {
+    len: usize,
+    chunks: [Chunk; CHUNKS],
+}
+
+impl PersistentBitmap {
+    pub fn new() -> Self {
+        // all the chunks of an empty bitmap are shared
+        let empty = Arc::new([0; CHUNK_WORDS]);
+        PersistentBitmap {
+            len: 0,
+            chunks: core::array::from_fn(|_| empty.clone()),
+        }
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Returns the chunk and the position in the chunk of the word containing `value`.
+    #[inline]
+    fn locate(value: u16) -> (usize, usize, usize) {
+        let key = Bitmap::key(value);
+        (
+            key / CHUNK_WORDS,
+            key % CHUNK_WORDS,
+            Bitmap::bit_offset(value),
+        )
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        let (chunk, key, bit) = Self::locate(value);
+        self.chunks[chunk][key] & (1 << bit) != 0
+    }
+
+    /// Returns `true` if the value was not already present in the bitmap.
+    /// The chunk of the value is copied if it's shared with another version
+    /// and the value is absent.
+    pub fn insert(&mut self, value: u16) -> bool {
+        if self.contains(value) {
+            return false;
+        }
+        let (chunk, key, bit) = Self::locate(value);
+        Arc::make_mut(&mut self.chunks[chunk])[key] |= 1 << bit;
+        self.len += 1;
+        true
+    }
+
+    /// Returns `true` if the value was present in the bitmap.
+    /// The chunk of the value is copied if it's shared with another version
+    /// and the value is present.
+    pub fn remove(&mut self, value: u16) -> bool {
+        if !self.contains(value) {
+            return false;
+        }
+        let (chunk, key, bit) = Self::locate(value);
+        Arc::make_mut(&mut self.chunks[chunk])[key] &= !(1 << bit);
+        self.len -= 1;
+        true
+    }
+
+    /// Returns the number of chunks shared between both versions.
+    pub fn shared_chunks(&self, other: &Self) -> usize {
+        self.chunks
+            .iter()
+            .zip(&other.chunks)
+            .filter(|(left, right)| Arc::ptr_eq(left, right))
+            .count()
+    }
+
+    /// Iterates over the values of the bitmap in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u16> + '_ {
+        Values::new(
+            self.chunks.iter().flat_map(|chunk| chunk.iter().copied()),
+            0,
+        )
+    }
+
+    /// Copies the version in a regular bitmap.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut words = [0; Bitmap::BITMAP_SIZE];
+        for (words, chunk) in words.chunks_exact_mut(CHUNK_WORDS).zip(&self.chunks) {
+            words.copy_from_slice(&chunk[..]);
+        }
+        Bitmap::from_words(words)
+    }
+}
+
+impl From<&Bitmap> for PersistentBitmap {
+    fn from(bitmap: &Bitmap) -> Self {
+        let mut persistent = PersistentBitmap::new();
+        for (chunk, words) in persistent
+            .chunks
+            .iter_mut()
+            .zip(bitmap.store.chunks_exact(CHUNK_WORDS))
+        {
+            if words.iter().any(|word| *word != 0) {
+                *chunk = Arc::new(words.try_into().unwrap());
+            }
+        }
+        persistent.len = bitmap.len();
+        persistent
+    }
+}
+
+impl Default for PersistentBitmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The versions sharing a chunk don't compare its words.
+impl PartialEq for PersistentBitmap {
+    fn eq(&self, other: &Self) -> bool {
+        self.len == other.len
+            && self
+                .chunks
+                .iter()
+                .zip(&other.chunks)
+                .all(|(left, right)| Arc::ptr_eq(left, right) || left == right)
+    }
+}
+
+impl Eq for PersistentBitmap {}
+
+impl fmt::Debug for PersistentBitmap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.to_bitmap().fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn versions() {
+        let mut v1 = PersistentBitmap::new();
+        assert!(v1.insert(3));
+        assert!(!v1.insert(3));
+        assert!(v1.insert(u16::MAX));
+        let mut v2 = v1.clone();
+        assert_eq!(v1.shared_chunks(&v2), CHUNKS);
+
+        // only the chunk of the value is copied
+        assert!(v2.insert(4));
+        assert!(v2.remove(u16::MAX));
+        assert!(!v2.remove(u16::MAX));
+        assert_eq!(v1.shared_chunks(&v2), CHUNKS - 2);
+
+        assert_eq!(v1.iter().collect::<Vec<_>>(), [3, u16::MAX]);
+        assert_eq!(v2.iter().collect::<Vec<_>>(), [3, 4]);
+        assert_eq!(v1.len(), 2);
+        assert_eq!(v2.to_bitmap(), Bitmap::from_iter([3, 4]));
+        assert!(v2.remove(4));
+        assert_ne!(v1, v2);
+        assert!(v2.insert(u16::MAX));
+        assert_eq!(v1, v2);
+    }
+
+    #[test]
+    fn from_bitmap() {
+        let bitmap = Bitmap::from_iter([0, 5000, u16::MAX]);
+        let persistent = PersistentBitmap::from(&bitmap);
+        assert_eq!(persistent.len(), 3);
+        assert_eq!(persistent.to_bitmap(), bitmap);
+        let mut updated = persistent.clone();
+        assert!(updated.insert(1));
+        assert_eq!(persistent.shared_chunks(&updated), CHUNKS - 1);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_persistent(bitmap in crate::strategies::any(), updates in prop::collection::vec((any::<bool>(), any::<u16>()), 0..50)) {
+            let mut persistent = PersistentBitmap::from(&bitmap);
+            let snapshot = persistent.clone();
+            let mut expected = bitmap.clone();
+            for (insert, value) in updates {
+                if insert {
+                    assert_eq!(persistent.insert(value), expected.insert(value));
+                } else {
+                    assert_eq!(persistent.remove(value), expected.remove(value));
+                }
+            }
+            assert_eq!(persistent.to_bitmap(), expected);
+            assert_eq!(persistent.len(), expected.len());
+            assert_eq!(snapshot.to_bitmap(), bitmap);
+            assert!(persistent.iter().eq(expected.iter()));
+        }
+    }
+}
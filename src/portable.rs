@@ -0,0 +1,64 @@
+//! The kernels vectorized with the portable SIMD types of `std::simd`, which
+//! are only available on nightly.
+
+use std::simd::num::SimdUint;
+use std::simd::Simd;
+
+use crate::backend::Backend;
+use crate::Word;
+
+/// Number of words processed at once, the compiler splits the vectors in as
+/// many registers as needed by the target.
+const LANE_WORDS: usize = 4;
+
+type Lane = Simd<Word, LANE_WORDS>;
+
+/// The backend of every target, through the vectors of `std::simd`.
+#[derive(Clone, Copy)]
+pub(crate) struct Portable;
+
+impl Backend for Portable {
+    type Lane = Lane;
+
+    const LANE_WORDS: usize = LANE_WORDS;
+
+    #[inline(always)]
+    fn load(self, words: &[Word]) -> Lane {
+        Lane::from_slice(words)
+    }
+
+    #[inline(always)]
+    fn store(self, lane: Lane, words: &mut [Word]) {
+        lane.copy_to_slice(words)
+    }
+
+    #[inline(always)]
+    fn and(self, left: Lane, right: Lane) -> Lane {
+        left & right
+    }
+
+    #[inline(always)]
+    fn or(self, left: Lane, right: Lane) -> Lane {
+        left | right
+    }
+
+    #[inline(always)]
+    fn and_not(self, left: Lane, right: Lane) -> Lane {
+        left & !right
+    }
+
+    #[inline(always)]
+    fn xor(self, left: Lane, right: Lane) -> Lane {
+        left ^ right
+    }
+
+    #[inline(always)]
+    fn not(self, lane: Lane) -> Lane {
+        !lane
+    }
+
+    #[inline(always)]
+    fn count_ones(self, lane: Lane) -> usize {
+        lane.count_ones().reduce_sum() as usize
+    }
+}
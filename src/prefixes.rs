@@ -0,0 +1,134 @@
+use core::fmt;
+
+use crate::iter::Values;
+use crate::{Bitmap, Word};
+
+/// Number of words of a [`Bitmap8`].
+const WORDS: usize = 256 / Word::BITS as usize;
+
+/// A bitmap of the 256 `u8`, the low bytes of a block of values returned by
+/// [`Bitmap::iter_prefixes`].
+#[derive(Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Bitmap8 {
+    store: [Word; WORDS],
+}
+
+impl Bitmap8 {
+    pub const fn new() -> Self {
+        Bitmap8 { store: [0; WORDS] }
+    }
+
+    #[inline]
+    pub fn words(&self) -> &[Word; WORDS] {
+        &self.store
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.store
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.store.iter().all(|word| *word == 0)
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u8) -> bool {
+        self.store[value as usize / Word::BITS as usize] & (1 << (value as u32 % Word::BITS)) != 0
+    }
+
+    /// Returns `true` if the value was not already present in the bitmap.
+    #[inline]
+    pub fn insert(&mut self, value: u8) -> bool {
+        let inserted = !self.contains(value);
+        self.store[value as usize / Word::BITS as usize] |= 1 << (value as u32 % Word::BITS);
+        inserted
+    }
+
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn remove(&mut self, value: u8) -> bool {
+        let removed = self.contains(value);
+        self.store[value as usize / Word::BITS as usize] &= !(1 << (value as u32 % Word::BITS));
+        removed
+    }
+
+    /// Iterates over the values of the bitmap in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = u8> + '_ {
+        Values::new(self.store.iter().copied(), 0).map(|value| value as u8)
+    }
+}
+
+impl FromIterator<u8> for Bitmap8 {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        let mut bitmap = Bitmap8::new();
+        iter.into_iter().for_each(|value| {
+            bitmap.insert(value);
+        });
+        bitmap
+    }
+}
+
+impl fmt::Debug for Bitmap8 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl Bitmap {
+    /// Iterates over the non-empty blocks of 256 values in increasing order,
+    /// along with the high byte shared by all the values of the block. The
+    /// value `v` is in the block `v >> 8` as `v as u8`.
+    pub fn iter_prefixes(&self) -> impl Iterator<Item = (u8, Bitmap8)> + '_ {
+        let (blocks, []) = self.store.as_chunks::<WORDS>() else {
+            unreachable!()
+        };
+        blocks
+            .iter()
+            .enumerate()
+            .filter(|(_, block)| block.iter().any(|word| *word != 0))
+            .map(|(prefix, block)| (prefix as u8, Bitmap8 { store: *block }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn iter_prefixes() {
+        let bitmap = Bitmap::from_iter([0, 1, 255, 256, 1000, u16::MAX]);
+        let prefixes: Vec<(u8, Bitmap8)> = bitmap.iter_prefixes().collect();
+        insta::assert_snapshot!(format!("{prefixes:?}"), @"[(0, {0, 1, 255}), (1, {0}), (3, {232}), (255, {255})]");
+        assert_eq!(Bitmap::new().iter_prefixes().count(), 0);
+        assert_eq!(Bitmap::full().iter_prefixes().count(), 256);
+
+        let mut block = Bitmap8::new();
+        assert!(block.is_empty());
+        assert!(block.insert(200));
+        assert!(!block.insert(200));
+        assert!(block.contains(200));
+        assert!(!block.remove(3));
+        assert!(block.remove(200));
+        assert_eq!(block.len(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_iter_prefixes(bitmap in crate::strategies::any()) {
+            let values: Vec<u16> = bitmap
+                .iter_prefixes()
+                .flat_map(|(prefix, block)| {
+                    assert!(!block.is_empty());
+                    block.iter().map(move |low| (prefix as u16) << 8 | low as u16).collect::<Vec<_>>()
+                })
+                .collect();
+            assert_eq!(values, bitmap.to_vec());
+        }
+    }
+}
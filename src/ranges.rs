@@ -0,0 +1,622 @@
+use core::fmt;
+use core::ops::{Bound, Range, RangeBounds, RangeInclusive};
+
+use crate::iter::Values;
+use crate::{Bitmap, Word};
+
+/// Converts any range of values in the half-open range of the bits it covers,
+/// the end being at most 65536 so an inclusive end of 65535 can be represented.
+/// The reversed ranges become empty.
+#[inline]
+pub(crate) fn normalize_range(range: impl RangeBounds<u16>) -> Range<usize> {
+    let start = match range.start_bound() {
+        Bound::Included(&start) => start as usize,
+        Bound::Excluded(&start) => start as usize + 1,
+        Bound::Unbounded => 0,
+    };
+    let end = match range.end_bound() {
+        Bound::Included(&end) => end as usize + 1,
+        Bound::Excluded(&end) => end as usize,
+        Bound::Unbounded => u16::MAX as usize + 1,
+    };
+    start..end.max(start)
+}
+
+/// Iterates over the keys of the words overlapping a range returned by
+/// [`normalize_range`], along with the mask of the bits of the word in the range.
+#[inline]
+pub(crate) fn range_masks(range: Range<usize>) -> impl Iterator<Item = (usize, Word)> {
+    let bits = Word::BITS as usize;
+    let keys = if range.is_empty() {
+        0..0
+    } else {
+        range.start / bits..(range.end - 1) / bits + 1
+    };
+    keys.map(move |key| {
+        let low = range.start.max(key * bits) - key * bits;
+        let high = range.end.min((key + 1) * bits) - key * bits;
+        (key, word_mask(low, high))
+    })
+}
+
+/// Returns the mask of the bits of a word covering the values `start..end`
+/// relative to the word, `end` being at most 64.
+#[inline]
+fn word_mask(start: usize, end: usize) -> Word {
+    debug_assert!(start <= end && end <= Word::BITS as usize);
+    if start == end {
+        return 0;
+    }
+    (Word::MAX >> (Word::BITS as usize - (end - start))) << start
+}
+
+/// Returns the longest run of set bits in `words`, the full words are
+/// extended at once and the other ones are walked run by run.
+fn longest_run(words: impl Iterator<Item = Word>) -> Option<RangeInclusive<u16>> {
+    let bits = Word::BITS as usize;
+    let mut longest: Option<Range<usize>> = None;
+    let mut current = 0..0;
+    let mut push = |run: Range<usize>| {
+        if run.start == current.end {
+            current.end = run.end;
+            return;
+        }
+        if longest
+            .as_ref()
+            .map_or(!current.is_empty(), |l| current.len() > l.len())
+        {
+            longest = Some(current.clone());
+        }
+        current = run;
+    };
+    for (key, word) in words.enumerate() {
+        let base = key * bits;
+        if word == Word::MAX {
+            push(base..base + bits);
+            continue;
+        }
+        let mut pos = 0;
+        while pos < bits {
+            pos += (word >> pos).trailing_zeros() as usize;
+            if pos >= bits {
+                break;
+            }
+            let len = (word >> pos).trailing_ones() as usize;
+            push(base + pos..base + pos + len);
+            pos += len;
+        }
+    }
+    // flush the last run
+    push(usize::MAX..usize::MAX);
+    longest.map(|run| run.start as u16..=(run.end - 1) as u16)
+}
+
+impl Bitmap {
+    /// Returns the number of values in each of the `ranges`, in the order of
+    /// the `ranges`. The store is walked only once to compute the cumulative
+    /// number of values before every word, then each range only needs to look
+    /// at the two words containing its bounds.
+    pub fn ranges_cardinality<R: RangeBounds<u16>>(&self, ranges: &[R]) -> Vec<usize> {
+        let bits = Word::BITS as usize;
+        // cumulative[i] is the number of values in the words before the word `i`
+        let mut cumulative = Vec::with_capacity(Self::BITMAP_SIZE + 1);
+        cumulative.push(0);
+        let mut count = 0;
+        for word in self.store {
+            count += word.count_ones() as usize;
+            cumulative.push(count);
+        }
+        // number of values lower than `value`, with `value` up to 65536
+        let rank = |value: usize| {
+            let (key, bit) = (value / bits, value % bits);
+            let head = cumulative[key];
+            if bit == 0 {
+                head
+            } else {
+                head + (self.store[key] & word_mask(0, bit)).count_ones() as usize
+            }
+        };
+
+        ranges
+            .iter()
+            .map(|range| {
+                let range = normalize_range((range.start_bound(), range.end_bound()));
+                rank(range.end) - rank(range.start)
+            })
+            .collect()
+    }
+
+    /// Returns the values of the bitmap contained in each of the `ranges`, in
+    /// the order of the `ranges`.
+    pub fn values_in_ranges<R: RangeBounds<u16>>(&self, ranges: &[R]) -> Vec<Vec<u16>> {
+        let counts = self.ranges_cardinality(ranges);
+        ranges
+            .iter()
+            .zip(counts)
+            .map(|(range, count)| {
+                let mut values = Vec::with_capacity(count);
+                values.extend(self.iter_range((range.start_bound(), range.end_bound())));
+                values
+            })
+            .collect()
+    }
+
+    /// Returns the number of values contained in `range`.
+    pub fn range_cardinality(&self, range: impl RangeBounds<u16>) -> usize {
+        range_masks(normalize_range(range))
+            .map(|(key, mask)| (self.store[key] & mask).count_ones() as usize)
+            .sum()
+    }
+
+    /// Iterates over the values contained in `range` in increasing order.
+    pub fn iter_range(&self, range: impl RangeBounds<u16>) -> impl Iterator<Item = u16> + '_ {
+        let range = normalize_range(range);
+        let first_key = range.start / Word::BITS as usize;
+        let words = range_masks(range).map(|(key, mask)| self.store[key] & mask);
+        Values::new(words, first_key)
+    }
+
+    /// Removes all the values contained in `range` from the bitmap and returns
+    /// them in a new bitmap.
+    pub fn extract_range(&mut self, range: impl RangeBounds<u16>) -> Bitmap {
+        let mut extracted = Bitmap::new();
+        for (key, mask) in range_masks(normalize_range(range)) {
+            extracted.store[key] = self.store[key] & mask;
+            self.store[key] &= !mask;
+            extracted.len += extracted.store[key].count_ones() as usize;
+        }
+        self.len -= extracted.len;
+        self.generation.bump();
+        self.debug_assert_invariants();
+        extracted.debug_assert_invariants();
+        extracted
+    }
+
+    /// Inserts all the values contained in `range`. The words inside the range
+    /// are set whole, only the words of its bounds are masked.
+    pub fn insert_range(&mut self, range: impl RangeBounds<u16>) {
+        for (key, mask) in range_masks(normalize_range(range)) {
+            self.len += (!self.store[key] & mask).count_ones() as usize;
+            self.store[key] |= mask;
+        }
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Removes all the values contained in `range`. Unlike [`Bitmap::extract_range`]
+    /// the removed values are only counted, not returned.
+    pub fn remove_range(&mut self, range: impl RangeBounds<u16>) {
+        for (key, mask) in range_masks(normalize_range(range)) {
+            self.len -= (self.store[key] & mask).count_ones() as usize;
+            self.store[key] &= !mask;
+        }
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Flips all the values contained in `range`: the present ones are removed
+    /// and the absent ones are inserted, i.e. the complement within `range`.
+    pub fn flip_range(&mut self, range: impl RangeBounds<u16>) {
+        for (key, mask) in range_masks(normalize_range(range)) {
+            let present = (self.store[key] & mask).count_ones() as usize;
+            self.len = self.len - present + (mask.count_ones() as usize - present);
+            self.store[key] ^= mask;
+        }
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Returns the number of values in every block of `block_bits` consecutive
+    /// values: the first entry counts the values in `0..block_bits`, the second
+    /// in `block_bits..2 * block_bits`, etc.
+    ///
+    /// # Panics
+    ///
+    /// If `block_bits` is not a power of two between 1 and 65536.
+    pub fn density_histogram(&self, block_bits: usize) -> Vec<u32> {
+        assert!(
+            block_bits.is_power_of_two() && block_bits <= u16::MAX as usize + 1,
+            "the block size must be a power of two between 1 and 65536, got {block_bits}"
+        );
+        let bits = Word::BITS as usize;
+        if block_bits >= bits {
+            self.store
+                .chunks(block_bits / bits)
+                .map(|words| words.iter().map(|word| word.count_ones()).sum())
+                .collect()
+        } else {
+            let mask = word_mask(0, block_bits);
+            self.store
+                .iter()
+                .flat_map(|word| {
+                    (0..bits)
+                        .step_by(block_bits)
+                        .map(move |shift| ((word >> shift) & mask).count_ones())
+                })
+                .collect()
+        }
+    }
+
+    /// Iterates over the runs of consecutive values of the bitmap in increasing order.
+    ///
+    /// The runs are found with word tricks rather than bit by bit: the empty
+    /// and full words are skipped whole, and `x & (x + (x & -x))` clears the
+    /// lowest run of a word at once.
+    pub fn ranges(&self) -> impl Iterator<Item = RangeInclusive<u16>> + '_ {
+        let bits = Word::BITS as usize;
+        let mut key = 0;
+        // the bits of `store[key]` not yet returned
+        let mut word = self.store[0];
+        core::iter::from_fn(move || {
+            while word == 0 {
+                key += 1;
+                word = *self.store.get(key)?;
+            }
+            let lowest = word & word.wrapping_neg();
+            let start = key * bits + word.trailing_zeros() as usize;
+            // the lowest run is cleared, the addition overflows when it reaches the last bit
+            let rest = word & word.wrapping_add(lowest);
+            if rest != 0 || word.leading_ones() == 0 {
+                let end = key * bits + (word ^ rest).ilog2() as usize;
+                word = rest;
+                return Some(start as u16..=end as u16);
+            }
+
+            // the run continues in the next words
+            key += 1;
+            while self.store.get(key) == Some(&Word::MAX) {
+                key += 1;
+            }
+            let Some(&next) = self.store.get(key) else {
+                word = 0;
+                return Some(start as u16..=u16::MAX);
+            };
+            let end = key * bits + next.trailing_ones() as usize - 1;
+            word = next & next.wrapping_add(1);
+            Some(start as u16..=end as u16)
+        })
+    }
+
+    /// Returns the longest run of consecutive values of the bitmap, the first
+    /// one if several runs have the same length, or `None` if it's empty.
+    pub fn longest_run(&self) -> Option<RangeInclusive<u16>> {
+        longest_run(self.store.iter().copied())
+    }
+
+    /// Returns the longest run of consecutive values absent from the bitmap,
+    /// the first one if several gaps have the same length, or `None` if it's full.
+    pub fn largest_gap(&self) -> Option<RangeInclusive<u16>> {
+        longest_run(self.store.iter().map(|word| !word))
+    }
+
+    /// Returns the runs of consecutive values of the bitmap in increasing order.
+    pub fn to_ranges(&self) -> Vec<RangeInclusive<u16>> {
+        self.ranges().collect()
+    }
+
+    /// Writes the runs of consecutive values of the bitmap as a comma-separated
+    /// list of values and inclusive ranges, like `1-5, 12, 20-21`, without any
+    /// intermediate allocation.
+    pub fn write_ranges(&self, w: &mut impl fmt::Write) -> fmt::Result {
+        for (i, range) in self.ranges().enumerate() {
+            if i != 0 {
+                w.write_str(", ")?;
+            }
+            if range.start() == range.end() {
+                write!(w, "{}", range.start())?;
+            } else {
+                write!(w, "{}-{}", range.start(), range.end())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn ranges() {
+        let bitmap = Bitmap::from_iter((0..100).chain(1000..1010).chain([u16::MAX - 1, u16::MAX]));
+        let ranges = [1000..2000, 0..10, 60..70, 5..5, 0..u16::MAX];
+        insta::assert_debug_snapshot!(bitmap.ranges_cardinality(&ranges), @r###"
+        [
+            10,
+            10,
+            10,
+            0,
+            111,
+        ]
+        "###);
+        insta::assert_debug_snapshot!(bitmap.values_in_ranges(&[62..66, u16::MAX - 2..u16::MAX]), @r###"
+        [
+            [
+                62,
+                63,
+                64,
+                65,
+            ],
+            [
+                65534,
+            ],
+        ]
+        "###);
+    }
+
+    #[test]
+    fn extract_range() {
+        let mut bitmap = Bitmap::from_iter((0..100).chain([1000, u16::MAX]));
+        let extracted = bitmap.extract_range(60..1001);
+        insta::assert_debug_snapshot!(extracted.len(), @"41");
+        insta::assert_debug_snapshot!(bitmap.len(), @"61");
+        assert!(bitmap.iter().eq((0..60).chain([u16::MAX])));
+        assert!(extracted.iter().eq((60..100).chain([1000])));
+
+        assert!(bitmap.extract_range(10..10).is_empty());
+        assert_eq!(bitmap.extract_range(0..u16::MAX).len(), 60);
+        assert_eq!(bitmap.to_vec(), [u16::MAX]);
+        assert_eq!(bitmap.extract_range(..).to_vec(), [u16::MAX]);
+        assert!(bitmap.is_empty());
+    }
+
+    #[test]
+    fn insert_range() {
+        let mut bitmap = Bitmap::from_iter([3, 70, 1000]);
+        bitmap.insert_range(60..=129);
+        assert_eq!(bitmap.len(), 72);
+        assert!(bitmap
+            .iter()
+            .eq([3].into_iter().chain(60..=129).chain([1000])));
+        bitmap.insert_range(10..10);
+        assert_eq!(bitmap.len(), 72);
+        bitmap.insert_range(u16::MAX..);
+        assert!(bitmap.contains(u16::MAX));
+        bitmap.insert_range(..);
+        assert_eq!(bitmap, Bitmap::full());
+    }
+
+    #[test]
+    fn remove_range() {
+        let mut bitmap = Bitmap::from_iter((0..200).chain([1000, u16::MAX]));
+        bitmap.remove_range(60..=129);
+        assert_eq!(bitmap.len(), 132);
+        assert!(bitmap
+            .iter()
+            .eq((0..60).chain(130..200).chain([1000, u16::MAX])));
+        bitmap.remove_range(u16::MAX..);
+        assert!(!bitmap.contains(u16::MAX));
+        bitmap.remove_range(..);
+        assert!(bitmap.is_empty());
+    }
+
+    #[test]
+    fn flip_range() {
+        let mut bitmap = Bitmap::from_iter((0..100).chain([1000, u16::MAX]));
+        bitmap.flip_range(50..=149);
+        assert_eq!(bitmap.len(), 102);
+        assert!(bitmap
+            .iter()
+            .eq((0..50).chain(100..150).chain([1000, u16::MAX])));
+        bitmap.flip_range(50..=149);
+        assert!(bitmap.iter().eq((0..100).chain([1000, u16::MAX])));
+        bitmap.flip_range(..);
+        assert_eq!(bitmap.len(), u16::MAX as usize + 1 - 102);
+    }
+
+    #[test]
+    fn range_bounds() {
+        assert_eq!(normalize_range(..), 0..65536);
+        assert_eq!(normalize_range(..=u16::MAX), 0..65536);
+        assert_eq!(normalize_range(u16::MAX..), 65535..65536);
+        assert_eq!(normalize_range(12..=12), 12..13);
+        assert_eq!(
+            normalize_range((Bound::Excluded(u16::MAX), Bound::Unbounded)),
+            65536..65536
+        );
+        #[allow(clippy::reversed_empty_ranges)]
+        let reversed = normalize_range(10..5);
+        assert!(reversed.is_empty());
+
+        let bitmap = Bitmap::from_iter((0..100).chain([1000, u16::MAX - 1, u16::MAX]));
+        assert_eq!(bitmap.range_cardinality(..), bitmap.len());
+        assert_eq!(bitmap.range_cardinality(u16::MAX..), 1);
+        assert_eq!(bitmap.range_cardinality(..=u16::MAX), 103);
+        assert_eq!(bitmap.range_cardinality(63..=64), 2);
+        assert!(bitmap
+            .iter_range(90..)
+            .eq((90..100).chain([1000, u16::MAX - 1, u16::MAX])));
+        assert!(bitmap.iter_range(..=3).eq(0..=3));
+        assert_eq!(bitmap.iter_range(100..1000).next(), None);
+        assert_eq!(bitmap.ranges_cardinality(&[0.., 1000..]), [103, 3]);
+    }
+
+    #[test]
+    fn density_histogram() {
+        let bitmap = Bitmap::from_iter((0..300).chain(1000..1010).chain([u16::MAX]));
+        let histogram = bitmap.density_histogram(256);
+        assert_eq!(histogram.len(), 256);
+        insta::assert_debug_snapshot!(histogram[..5], @r###"
+        [
+            256,
+            44,
+            0,
+            10,
+            0,
+        ]
+        "###);
+        assert_eq!(histogram[255], 1);
+
+        let histogram = bitmap.density_histogram(8);
+        assert_eq!(histogram.len(), 8192);
+        assert_eq!(histogram[37..39], [4, 0]);
+        assert_eq!(Bitmap::full().density_histogram(65536), [65536]);
+        assert!(Bitmap::full()
+            .density_histogram(1)
+            .iter()
+            .all(|count| *count == 1));
+    }
+
+    #[test]
+    #[should_panic = "the block size must be a power of two between 1 and 65536, got 100"]
+    fn density_histogram_bad_block() {
+        Bitmap::new().density_histogram(100);
+    }
+
+    #[test]
+    fn write_ranges() {
+        let bitmap = Bitmap::from_iter((1..=5).chain([12, 20, 21]).chain(65000..=u16::MAX));
+        let mut s = String::new();
+        bitmap.write_ranges(&mut s).unwrap();
+        insta::assert_snapshot!(s, @"1-5, 12, 20-21, 65000-65535");
+        insta::assert_debug_snapshot!(bitmap.to_ranges(), @r###"
+        [
+            1..=5,
+            12..=12,
+            20..=21,
+            65000..=65535,
+        ]
+        "###);
+
+        let mut s = String::new();
+        Bitmap::new().write_ranges(&mut s).unwrap();
+        assert!(s.is_empty());
+        assert_eq!(Bitmap::full().to_ranges(), [0..=u16::MAX]);
+    }
+
+    #[test]
+    fn ranges_across_words() {
+        let ranges = [
+            0..=0,
+            2..=63,
+            128..=319,
+            383..=384,
+            512..=512,
+            575..=575,
+            65472..=65534,
+        ];
+        let bitmap = Bitmap::from_iter(ranges.iter().cloned().flatten());
+        assert_eq!(bitmap.to_ranges(), ranges);
+
+        let ranges = [63..=63, 65535..=65535];
+        assert_eq!(
+            Bitmap::from_iter(ranges.iter().cloned().flatten()).to_ranges(),
+            ranges
+        );
+        let ranges = [1..=65535];
+        assert_eq!(
+            Bitmap::from_iter(ranges.iter().cloned().flatten()).to_ranges(),
+            ranges
+        );
+    }
+
+    #[test]
+    fn longest_run() {
+        let bitmap =
+            Bitmap::from_iter((1..=5).chain(60..=130).chain([200]).chain(65000..=u16::MAX));
+        assert_eq!(bitmap.longest_run(), Some(65000..=u16::MAX));
+        assert_eq!(bitmap.largest_gap(), Some(201..=64999));
+        let bitmap = Bitmap::from_iter((1..=5).chain(10..=14).chain([20]));
+        assert_eq!(bitmap.longest_run(), Some(1..=5));
+        assert_eq!(bitmap.largest_gap(), Some(21..=u16::MAX));
+
+        assert_eq!(Bitmap::new().longest_run(), None);
+        assert_eq!(Bitmap::new().largest_gap(), Some(0..=u16::MAX));
+        assert_eq!(Bitmap::full().longest_run(), Some(0..=u16::MAX));
+        assert_eq!(Bitmap::full().largest_gap(), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ranges(bitmap in crate::strategies::any(), ranges in prop::collection::vec((0..=u16::MAX, 0..=u16::MAX), 0..10)) {
+            let ranges: Vec<_> = ranges.into_iter().map(|(start, end)| start..end).collect();
+            let counts = bitmap.ranges_cardinality(&ranges);
+            let values = bitmap.values_in_ranges(&ranges);
+            for ((range, count), values) in ranges.iter().zip(counts).zip(values) {
+                let expected: Vec<u16> = bitmap.iter().filter(|v| range.contains(v)).collect();
+                assert_eq!(count, expected.len());
+                assert_eq!(values, expected);
+            }
+        }
+
+        #[test]
+        fn prop_range_bounds(bitmap in crate::strategies::any(), start in 0..=u16::MAX, end in 0..=u16::MAX) {
+            let expected: Vec<u16> = bitmap.iter().filter(|v| (start..=end).contains(v)).collect();
+            assert_eq!(bitmap.iter_range(start..=end).collect::<Vec<_>>(), expected);
+            assert_eq!(bitmap.range_cardinality(start..=end), expected.len());
+
+            let expected: Vec<u16> = bitmap.iter().filter(|v| *v >= start).collect();
+            assert_eq!(bitmap.iter_range(start..).collect::<Vec<_>>(), expected);
+            assert_eq!(bitmap.range_cardinality(..start), bitmap.len() - expected.len());
+        }
+
+        #[test]
+        fn prop_density_histogram(bitmap in crate::strategies::any(), shift in 0..=16_u32) {
+            let block_bits = 1 << shift;
+            let histogram = bitmap.density_histogram(block_bits);
+            assert_eq!(histogram.len(), (u16::MAX as usize + 1) / block_bits);
+            let mut expected = vec![0; histogram.len()];
+            for value in bitmap.iter() {
+                expected[value as usize / block_bits] += 1;
+            }
+            assert_eq!(histogram, expected);
+        }
+
+        #[test]
+        fn prop_ranges_roundtrip(bitmap in crate::strategies::any()) {
+            let ranges = bitmap.to_ranges();
+            for pair in ranges.windows(2) {
+                assert!(*pair[0].end() as usize + 1 < *pair[1].start() as usize);
+            }
+            assert_eq!(Bitmap::from_iter(ranges.into_iter().flatten()), bitmap);
+        }
+
+        #[test]
+        fn prop_longest_run(bitmap in crate::strategies::any()) {
+            let longest = |ranges: Vec<RangeInclusive<u16>>| {
+                ranges.into_iter().rev().max_by_key(|range| range.end() - range.start())
+            };
+            assert_eq!(bitmap.longest_run(), longest(bitmap.to_ranges()));
+            let mut complement = Bitmap::full();
+            bitmap.iter().for_each(|value| { complement.remove(value); });
+            assert_eq!(bitmap.largest_gap(), longest(complement.to_ranges()));
+        }
+
+        #[test]
+        fn prop_insert_range(bitmap in crate::strategies::any(), start in 0..=u16::MAX, end in 0..=u16::MAX) {
+            let mut inserted = bitmap.clone();
+            inserted.insert_range(start..=end);
+            let expected = Bitmap::from_iter(bitmap.iter().chain(start..=end));
+            assert_eq!(inserted, expected);
+            inserted.assert_invariants();
+        }
+
+        #[test]
+        fn prop_remove_range(bitmap in crate::strategies::any(), start in 0..=u16::MAX, end in 0..=u16::MAX) {
+            let mut removed = bitmap.clone();
+            removed.remove_range(start..end);
+            let expected = Bitmap::from_iter(bitmap.iter().filter(|v| !(start..end).contains(v)));
+            assert_eq!(removed, expected);
+            removed.assert_invariants();
+        }
+
+        #[test]
+        fn prop_flip_range(bitmap in crate::strategies::any(), start in 0..=u16::MAX, end in 0..=u16::MAX) {
+            let mut flipped = bitmap.clone();
+            flipped.flip_range(start..end);
+            let expected = Bitmap::from_iter((0..=u16::MAX).filter(|v| bitmap.contains(*v) != (start..end).contains(v)));
+            assert_eq!(flipped, expected);
+            flipped.assert_invariants();
+        }
+
+        #[test]
+        fn prop_extract_range(bitmap in crate::strategies::any(), start in 0..=u16::MAX, end in 0..=u16::MAX) {
+            let mut remaining = bitmap.clone();
+            let extracted = remaining.extract_range(start..end);
+            let (inside, outside): (Vec<u16>, Vec<u16>) = bitmap.iter().partition(|v| (start..end).contains(v));
+            assert_eq!(extracted.to_vec(), inside);
+            assert_eq!(remaining.to_vec(), outside);
+        }
+    }
+}
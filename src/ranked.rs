@@ -0,0 +1,216 @@
+use core::ops::RangeBounds;
+
+use crate::ranges::normalize_range;
+use crate::select::select_in_word;
+use crate::{Bitmap, Word};
+
+/// Number of words summarized by an entry of the cumulative counts.
+const BLOCK_WORDS: usize = 32;
+const BLOCKS: usize = Bitmap::BITMAP_SIZE / BLOCK_WORDS;
+
+/// A bitmap maintaining the popcount of every word and the cumulative count of
+/// every block of 32 words on every mutation, so [`rank`](RankedBitmap::rank),
+/// [`select`](RankedBitmap::select) and [`range_cardinality`](RankedBitmap::range_cardinality)
+/// look at a few dozens of counters instead of the whole store.
+///
+/// The insertions and removals pay for it by updating up to 32 counters.
+#[derive(Clone)]
+pub struct RankedBitmap {
+    bitmap: Bitmap,
+    /// Number of values in every word.
+    counts: [u8; Bitmap::BITMAP_SIZE],
+    /// `blocks[i]` is the number of values in the blocks before the block `i`.
+    blocks: [u32; BLOCKS + 1],
+}
+
+impl RankedBitmap {
+    pub fn new() -> Self {
+        RankedBitmap::from(Bitmap::new())
+    }
+
+    #[inline]
+    pub fn bitmap(&self) -> &Bitmap {
+        &self.bitmap
+    }
+
+    #[inline]
+    pub fn into_bitmap(self) -> Bitmap {
+        self.bitmap
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.bitmap.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.bitmap.is_empty()
+    }
+
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        self.bitmap.contains(value)
+    }
+
+    /// Insert a value in the bitmap.
+    /// Returns `true` if the value was not already present in the bitmap.
+    #[inline]
+    pub fn insert(&mut self, value: u16) -> bool {
+        let inserted = self.bitmap.insert(value);
+        if inserted {
+            let key = Bitmap::key(value);
+            self.counts[key] += 1;
+            self.blocks[key / BLOCK_WORDS + 1..]
+                .iter_mut()
+                .for_each(|count| *count += 1);
+        }
+        inserted
+    }
+
+    /// Remove a value from the bitmap.
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn remove(&mut self, value: u16) -> bool {
+        let removed = self.bitmap.remove(value);
+        if removed {
+            let key = Bitmap::key(value);
+            self.counts[key] -= 1;
+            self.blocks[key / BLOCK_WORDS + 1..]
+                .iter_mut()
+                .for_each(|count| *count -= 1);
+        }
+        removed
+    }
+
+    /// Number of values lower than the bit `position`, which goes up to 65536.
+    #[inline]
+    fn rank_bit(&self, position: usize) -> usize {
+        let bits = Word::BITS as usize;
+        let (key, bit) = (position / bits, position % bits);
+        let block = key / BLOCK_WORDS;
+        let mut rank = self.blocks[block] as usize;
+        rank += self.counts[block * BLOCK_WORDS..key]
+            .iter()
+            .map(|count| *count as usize)
+            .sum::<usize>();
+        if bit != 0 {
+            rank += (self.bitmap.store[key] & ((1 << bit) - 1)).count_ones() as usize;
+        }
+        rank
+    }
+
+    /// Returns the number of values in the bitmap strictly lower than `value`,
+    /// see [`Bitmap::rank`].
+    #[inline]
+    pub fn rank(&self, value: u16) -> usize {
+        self.rank_bit(value as usize)
+    }
+
+    /// Returns the number of values contained in `range`, see [`Bitmap::range_cardinality`].
+    #[inline]
+    pub fn range_cardinality(&self, range: impl RangeBounds<u16>) -> usize {
+        let range = normalize_range(range);
+        self.rank_bit(range.end) - self.rank_bit(range.start)
+    }
+
+    /// Returns the `n`-th value of the bitmap (starting at 0), see [`Bitmap::select`].
+    pub fn select(&self, n: usize) -> Option<u16> {
+        if n >= self.len() {
+            return None;
+        }
+        // the last block starting with less than `n` values before it
+        let block = self.blocks.partition_point(|count| *count as usize <= n) - 1;
+        let mut remaining = n - self.blocks[block] as usize;
+        for key in block * BLOCK_WORDS..(block + 1) * BLOCK_WORDS {
+            let count = self.counts[key] as usize;
+            if remaining < count {
+                let bit = select_in_word(self.bitmap.store[key], remaining as u32);
+                return Some((key * Word::BITS as usize + bit as usize) as u16);
+            }
+            remaining -= count;
+        }
+        unreachable!("the cumulative counts are wrong")
+    }
+}
+
+impl Default for RankedBitmap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Bitmap> for RankedBitmap {
+    fn from(bitmap: Bitmap) -> Self {
+        let mut counts = [0; Bitmap::BITMAP_SIZE];
+        let mut blocks = [0; BLOCKS + 1];
+        for (key, word) in bitmap.store.iter().enumerate() {
+            counts[key] = word.count_ones() as u8;
+            blocks[key / BLOCK_WORDS + 1] += word.count_ones();
+        }
+        for block in 1..=BLOCKS {
+            blocks[block] += blocks[block - 1];
+        }
+        RankedBitmap {
+            bitmap,
+            counts,
+            blocks,
+        }
+    }
+}
+
+impl FromIterator<u16> for RankedBitmap {
+    fn from_iter<T: IntoIterator<Item = u16>>(iter: T) -> Self {
+        RankedBitmap::from(Bitmap::from_iter(iter))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn ranked() {
+        let mut bitmap = RankedBitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        assert_eq!(bitmap.rank(64), 3);
+        assert_eq!(bitmap.rank(u16::MAX), 5);
+        assert_eq!(bitmap.select(4), Some(1000));
+        assert_eq!(bitmap.select(6), None);
+        assert_eq!(bitmap.range_cardinality(..), 6);
+
+        assert!(bitmap.insert(2048));
+        assert!(!bitmap.insert(2048));
+        assert!(bitmap.remove(1));
+        assert!(!bitmap.remove(1));
+        assert_eq!(bitmap.select(4), Some(2048));
+        assert_eq!(bitmap.rank(u16::MAX), 5);
+        assert_eq!(bitmap.range_cardinality(1000..=2048), 2);
+        assert_eq!(bitmap.range_cardinality(u16::MAX..), 1);
+
+        let full = RankedBitmap::from(Bitmap::full());
+        assert_eq!(full.select(u16::MAX as usize), Some(u16::MAX));
+        assert_eq!(full.range_cardinality(..), u16::MAX as usize + 1);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_ranked(bitmap in crate::strategies::any(), ops in prop::collection::vec((any::<bool>(), any::<u16>()), 0..100), start in any::<u16>(), end in any::<u16>()) {
+            let mut bitmap = bitmap;
+            let mut ranked = RankedBitmap::from(bitmap.clone());
+            for (insert, value) in ops {
+                if insert {
+                    assert_eq!(ranked.insert(value), bitmap.insert(value));
+                } else {
+                    assert_eq!(ranked.remove(value), bitmap.remove(value));
+                }
+            }
+            assert_eq!(ranked.bitmap(), &bitmap);
+            for n in (0..=bitmap.len()).step_by(97) {
+                assert_eq!(ranked.select(n), bitmap.select(n));
+            }
+            assert_eq!(ranked.rank(start), bitmap.rank(start));
+            assert_eq!(ranked.range_cardinality(start..end), bitmap.range_cardinality(start..end));
+        }
+    }
+}
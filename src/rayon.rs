@@ -0,0 +1,132 @@
+use rayon::iter::plumbing::UnindexedConsumer;
+use rayon::iter::{Enumerate, FlatMapIter, IndexedParallelIterator, ParallelIterator};
+use rayon::prelude::*;
+use rayon::slice::Chunks;
+
+use crate::iter::Iter;
+use crate::{Bitmap, Word};
+
+/// Number of words of the store handled by a single task. The values of a
+/// block are yielded in order but the blocks are processed in parallel.
+const BLOCK_SIZE: usize = 16;
+
+type BlockValues<'a> = fn((usize, &'a [Word])) -> Iter<'a>;
+
+/// A parallel iterator over the values of a bitmap, see the
+/// [`IntoParallelIterator`] implementation of `&Bitmap`.
+pub struct ParIter<'a> {
+    inner: FlatMapIter<Enumerate<Chunks<'a, Word>>, BlockValues<'a>>,
+}
+
+impl ParallelIterator for ParIter<'_> {
+    type Item = u16;
+
+    fn drive_unindexed<C>(self, consumer: C) -> C::Result
+    where
+        C: UnindexedConsumer<Self::Item>,
+    {
+        self.inner.drive_unindexed(consumer)
+    }
+}
+
+/// Number of values inserted in a partial bitmap by a single task of [`Bitmap::from_slice_par`].
+const VALUES_PER_TASK: usize = 1 << 16;
+
+impl Bitmap {
+    /// Creates a bitmap from a large unsorted slice of values in parallel:
+    /// every task inserts a chunk of the slice in its own bitmap, then the
+    /// partial bitmaps are merged with unions.
+    pub fn from_slice_par(values: &[u16]) -> Self {
+        let mut bitmap = values
+            .par_chunks(VALUES_PER_TASK)
+            .fold(Bitmap::new, |mut bitmap, values| {
+                for value in values {
+                    // the length is recomputed by the union
+                    bitmap.insert_unchecked(*value);
+                }
+                bitmap
+            })
+            .reduce(Bitmap::new, |left, right| left | right);
+        // a single partial bitmap never goes through a union
+        bitmap.recompute_len();
+        bitmap
+    }
+}
+
+/// Iterates in parallel over the values of the bitmap:
+/// ```
+/// use bitmap::Bitmap;
+/// use rayon::prelude::*;
+///
+/// let bitmap = Bitmap::from_iter(0..1000);
+/// let sum: u64 = bitmap.par_iter().map(|value| value as u64).sum();
+/// assert_eq!(sum, 499_500);
+/// ```
+impl<'a> IntoParallelIterator for &'a Bitmap {
+    type Iter = ParIter<'a>;
+    type Item = u16;
+
+    fn into_par_iter(self) -> Self::Iter {
+        let values: BlockValues<'a> = |(block, words)| Iter::new(words, block * BLOCK_SIZE);
+        ParIter {
+            inner: self
+                .store
+                .par_chunks(BLOCK_SIZE)
+                .enumerate()
+                .flat_map_iter(values),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn par_iter() {
+        let bitmap = Bitmap::from_iter([0, 1, 63, 64, 1023, 1024, 1025, u16::MAX]);
+        let values: Vec<u16> = bitmap.par_iter().collect();
+        insta::assert_debug_snapshot!(values, @r###"
+        [
+            0,
+            1,
+            63,
+            64,
+            1023,
+            1024,
+            1025,
+            65535,
+        ]
+        "###);
+        assert_eq!(Bitmap::full().par_iter().count(), u16::MAX as usize + 1);
+        assert_eq!(Bitmap::new().par_iter().count(), 0);
+    }
+
+    #[test]
+    fn from_slice_par() {
+        let values: Vec<u16> = (0..1_000_000_u32)
+            .map(|i| (i.wrapping_mul(7919) % 50_000) as u16)
+            .collect();
+        let bitmap = Bitmap::from_slice_par(&values);
+        bitmap.assert_invariants();
+        assert_eq!(bitmap, Bitmap::from_iter(&values));
+        assert_eq!(Bitmap::from_slice_par(&[3, 1, 3]).to_vec(), [1, 3]);
+        assert!(Bitmap::from_slice_par(&[]).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_from_slice_par(values in prop::collection::vec(any::<u16>(), 0..1000)) {
+            let bitmap = Bitmap::from_slice_par(&values);
+            bitmap.assert_invariants();
+            assert_eq!(bitmap, Bitmap::from_iter(&values));
+        }
+
+        #[test]
+        fn prop_par_iter(bitmap in crate::strategies::any()) {
+            let values: Vec<u16> = bitmap.par_iter().collect();
+            assert_eq!(values, bitmap.to_vec());
+        }
+    }
+}
@@ -0,0 +1,149 @@
+use core::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+
+use crate::Bitmap;
+
+/// Number of values in the universe of a bitmap, and thus of weights.
+const UNIVERSE: usize = u16::MAX as usize + 1;
+
+impl Bitmap {
+    /// Returns the sum of the `weights` of the values of the bitmap.
+    ///
+    /// The weights are summed in increasing order of the values.
+    #[inline]
+    pub fn score(&self, weights: &[f32; UNIVERSE]) -> f32 {
+        let mut score = 0.0;
+        // indexing an array of 65536 elements with a `u16` never needs a bound check
+        self.visit(|value| score += weights[value as usize]);
+        score
+    }
+
+    /// Same as [`Bitmap::score`] with integer weights. The sum can't overflow.
+    #[inline]
+    pub fn score_int(&self, weights: &[i32; UNIVERSE]) -> i64 {
+        let mut score = 0;
+        self.visit(|value| score += weights[value as usize] as i64);
+        score
+    }
+
+    /// Returns the `k` values of the bitmap with the highest `scores`, from
+    /// the best to the worst. The scores are compared with [`f32::total_cmp`]
+    /// and the ties are broken in favor of the smallest values.
+    ///
+    /// Only the best `k` values seen so far are kept in a heap while visiting
+    /// the bitmap, nothing else is decoded nor sorted.
+    pub fn top_k_by(&self, k: usize, scores: &[f32; UNIVERSE]) -> Vec<u16> {
+        if k == 0 {
+            return Vec::new();
+        }
+        // a min-heap, the worst of the best values is on top
+        let mut heap = BinaryHeap::with_capacity(k.min(self.len()));
+        self.visit(|value| {
+            let candidate = Reverse(Candidate {
+                score: scores[value as usize],
+                value,
+            });
+            if heap.len() < k {
+                heap.push(candidate);
+            } else if let Some(mut worst) = heap.peek_mut() {
+                if candidate < *worst {
+                    *worst = candidate;
+                }
+            }
+        });
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|Reverse(candidate)| candidate.value)
+            .collect()
+    }
+}
+
+/// A value ordered by its score, the greatest candidate is the best one.
+struct Candidate {
+    score: f32,
+    value: u16,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.score
+            .total_cmp(&other.score)
+            .then_with(|| other.value.cmp(&self.value))
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn score() {
+        let mut weights = vec![0.0; UNIVERSE];
+        weights[0] = 1.5;
+        weights[64] = 2.0;
+        weights[u16::MAX as usize] = -0.5;
+        let weights: &[f32; UNIVERSE] = weights.as_slice().try_into().unwrap();
+
+        let bitmap = Bitmap::from_iter([0, 1, 64, u16::MAX]);
+        insta::assert_debug_snapshot!(bitmap.score(weights), @"3.0");
+        assert_eq!(Bitmap::new().score(weights), 0.0);
+
+        let weights: Vec<i32> = vec![i32::MAX; UNIVERSE];
+        let weights: &[i32; UNIVERSE] = weights.as_slice().try_into().unwrap();
+        assert_eq!(
+            Bitmap::full().score_int(weights),
+            i32::MAX as i64 * UNIVERSE as i64
+        );
+    }
+
+    #[test]
+    fn top_k_by() {
+        let mut scores = vec![0.0; UNIVERSE];
+        scores[3] = 5.0;
+        scores[64] = 7.5;
+        scores[1000] = -1.0;
+        scores[u16::MAX as usize] = 5.0;
+        let scores: &[f32; UNIVERSE] = scores.as_slice().try_into().unwrap();
+
+        let bitmap = Bitmap::from_iter([1, 3, 64, 1000, u16::MAX]);
+        assert_eq!(bitmap.top_k_by(3, scores), [64, 3, u16::MAX]);
+        assert_eq!(bitmap.top_k_by(10, scores), [64, 3, u16::MAX, 1, 1000]);
+        assert!(bitmap.top_k_by(0, scores).is_empty());
+        assert!(Bitmap::new().top_k_by(3, scores).is_empty());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_top_k_by(bitmap in crate::strategies::any(), k in 0..200_usize, seed in any::<u32>()) {
+            let scores: Vec<f32> = (0..UNIVERSE as u32).map(|i| (i.wrapping_mul(seed) % 1000) as f32).collect();
+            let scores: &[f32; UNIVERSE] = scores.as_slice().try_into().unwrap();
+            let mut expected = bitmap.to_vec();
+            expected.sort_by(|a, b| scores[*b as usize].total_cmp(&scores[*a as usize]).then(a.cmp(b)));
+            expected.truncate(k);
+            assert_eq!(bitmap.top_k_by(k, scores), expected);
+        }
+
+        #[test]
+        fn prop_score_int(bitmap in crate::strategies::any(), seed in any::<i32>()) {
+            let weights: Vec<i32> = (0..UNIVERSE as i32).map(|i| i.wrapping_mul(seed)).collect();
+            let weights: &[i32; UNIVERSE] = weights.as_slice().try_into().unwrap();
+            let expected: i64 = bitmap.iter().map(|value| weights[value as usize] as i64).sum();
+            assert_eq!(bitmap.score_int(weights), expected);
+        }
+    }
+}
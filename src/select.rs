@@ -0,0 +1,262 @@
+use crate::ranges::range_masks;
+use crate::{Bitmap, Iter, Word};
+
+/// Returns the position of the `n`-th bit set (starting at 0) in `word`.
+/// `n` must be lower than the number of bits set in `word`.
+#[inline]
+pub(crate) fn select_in_word(word: Word, n: u32) -> u32 {
+    debug_assert!(n < word.count_ones());
+    #[cfg(target_arch = "x86_64")]
+    {
+        if std::arch::is_x86_feature_detected!("bmi2") {
+            // safety: we just checked the CPU supports bmi2
+            return unsafe { select_in_word_bmi2(word, n) };
+        }
+    }
+    select_in_word_fallback(word, n)
+}
+
+/// Deposit a single bit on the `n`-th bit set of the word, then look where it landed.
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "bmi2")]
+#[inline]
+unsafe fn select_in_word_bmi2(word: Word, n: u32) -> u32 {
+    core::arch::x86_64::_pdep_u64(1 << n, word).trailing_zeros()
+}
+
+#[inline]
+fn select_in_word_fallback(mut word: Word, n: u32) -> u32 {
+    for _ in 0..n {
+        // clear the lowest bit set
+        word &= word - 1;
+    }
+    word.trailing_zeros()
+}
+
+impl Bitmap {
+    /// Returns the number of values in the bitmap strictly lower than `value`.
+    pub fn rank(&self, value: u16) -> usize {
+        let (key, bit) = (Self::key(value), Self::bit_offset(value));
+        let before: usize = self.store[..key]
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum();
+        let mask = (1 << bit) - 1;
+        before + (self.store[key] & mask).count_ones() as usize
+    }
+
+    /// Returns the `n`-th value of the bitmap (starting at 0) in increasing
+    /// order, or `None` if the bitmap contains `n` values or less.
+    pub fn select(&self, n: usize) -> Option<u16> {
+        if n >= self.len {
+            return None;
+        }
+        let mut remaining = n;
+        for (key, word) in self.store.iter().enumerate() {
+            let count = word.count_ones() as usize;
+            if remaining < count {
+                let bit = select_in_word(*word, remaining as u32);
+                return Some((key * Word::BITS as usize + bit as usize) as u16);
+            }
+            remaining -= count;
+        }
+        unreachable!("the length of the bitmap is wrong")
+    }
+
+    /// Returns at most `limit` values starting at the `offset`-th value of the
+    /// bitmap. The words before the page are skipped with their popcount, thus
+    /// the values of the previous pages are never decoded.
+    pub fn page(&self, offset: usize, limit: usize) -> Vec<u16> {
+        let mut page = Vec::with_capacity(limit.min(self.len.saturating_sub(offset)));
+        if offset >= self.len {
+            return page;
+        }
+        let mut remaining = offset;
+        for (key, word) in self.store.iter().enumerate() {
+            let count = word.count_ones() as usize;
+            if remaining < count {
+                let values = Iter::new(&self.store[key..], key);
+                page.extend(values.skip(remaining).take(limit));
+                break;
+            }
+            remaining -= count;
+        }
+        page
+    }
+
+    /// Splits the bitmap in `n` bitmaps of consecutive values holding the same
+    /// number of values, give or take one. The boundaries are found with
+    /// [`Bitmap::select`] and the words are copied all at once.
+    ///
+    /// # Panics
+    ///
+    /// If `n` is 0.
+    pub fn partition(&self, n: usize) -> Vec<Bitmap> {
+        assert!(n > 0, "can't partition a bitmap in 0 parts");
+        // the first value of every part, the last part ends at the end of the universe
+        let boundary = |part: usize| {
+            self.select(part * self.len / n)
+                .map_or(u16::MAX as usize + 1, |value| value as usize)
+        };
+        let mut start = 0;
+        (0..n)
+            .map(|part| {
+                let end = if part + 1 == n {
+                    u16::MAX as usize + 1
+                } else {
+                    boundary(part + 1)
+                };
+                let mut bitmap = Bitmap::new();
+                for (key, mask) in range_masks(start..end) {
+                    bitmap.store[key] = self.store[key] & mask;
+                }
+                bitmap.len = (part + 1) * self.len / n - part * self.len / n;
+                bitmap.debug_assert_invariants();
+                start = end;
+                bitmap
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn rank_select() {
+        let bitmap = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        insta::assert_debug_snapshot!((0..7).map(|n| bitmap.select(n)).collect::<Vec<_>>(), @r###"
+        [
+            Some(
+                0,
+            ),
+            Some(
+                1,
+            ),
+            Some(
+                63,
+            ),
+            Some(
+                64,
+            ),
+            Some(
+                1000,
+            ),
+            Some(
+                65535,
+            ),
+            None,
+        ]
+        "###);
+        assert_eq!(bitmap.rank(0), 0);
+        assert_eq!(bitmap.rank(64), 3);
+        assert_eq!(bitmap.rank(65), 4);
+        assert_eq!(bitmap.rank(u16::MAX), 5);
+        assert_eq!(Bitmap::full().select(u16::MAX as usize), Some(u16::MAX));
+    }
+
+    #[test]
+    fn page() {
+        let bitmap = Bitmap::from_iter((0..100).step_by(3).chain([1000, u16::MAX]));
+        insta::assert_debug_snapshot!(bitmap.page(30, 10), @r###"
+        [
+            90,
+            93,
+            96,
+            99,
+            1000,
+            65535,
+        ]
+        "###);
+        assert_eq!(bitmap.page(0, 3), [0, 3, 6]);
+        assert_eq!(bitmap.page(21, 2), [63, 66]);
+        assert!(bitmap.page(36, 10).is_empty());
+        assert!(bitmap.page(0, 0).is_empty());
+    }
+
+    #[test]
+    fn partition() {
+        let bitmap = Bitmap::from_iter((0..10).chain([1000, u16::MAX]));
+        insta::assert_debug_snapshot!(bitmap.partition(5), @r###"
+        [
+            {
+                0,
+                1,
+            },
+            {
+                2,
+                3,
+            },
+            {
+                4,
+                5,
+                6,
+            },
+            {
+                7,
+                8,
+            },
+            {
+                9,
+                1000,
+                65535,
+            },
+        ]
+        "###);
+        assert_eq!(bitmap.partition(1)[0], bitmap);
+        let parts = Bitmap::from_iter([3, 4]).partition(3);
+        assert_eq!(
+            parts.iter().map(|part| part.len()).collect::<Vec<_>>(),
+            [0, 1, 1]
+        );
+        assert!(Bitmap::new()
+            .partition(2)
+            .iter()
+            .all(|part| part.is_empty()));
+    }
+
+    #[test]
+    #[should_panic = "can't partition a bitmap in 0 parts"]
+    fn partition_zero() {
+        Bitmap::new().partition(0);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_partition(bitmap in crate::strategies::any(), n in 1..20_usize) {
+            let parts = bitmap.partition(n);
+            assert_eq!(parts.len(), n);
+            let min = parts.iter().map(|part| part.len()).min().unwrap();
+            let max = parts.iter().map(|part| part.len()).max().unwrap();
+            assert!(max - min <= 1);
+            let values: Vec<u16> = parts.iter().flat_map(|part| part.iter()).collect();
+            assert_eq!(values, bitmap.to_vec());
+        }
+
+        #[test]
+        fn prop_select_in_word(word in any::<Word>(), n in 0..64_u32) {
+            prop_assume!(n < word.count_ones());
+            let expected = select_in_word_fallback(word, n);
+            assert_eq!(select_in_word(word, n), expected);
+            assert_eq!((word & ((1 << expected) - 1)).count_ones(), n);
+            assert!(word & (1 << expected) != 0);
+        }
+
+        #[test]
+        fn prop_rank_select(bitmap in crate::strategies::any()) {
+            for (n, value) in bitmap.iter().enumerate().step_by(97) {
+                assert_eq!(bitmap.select(n), Some(value));
+                assert_eq!(bitmap.rank(value), n);
+            }
+            assert_eq!(bitmap.select(bitmap.len()), None);
+        }
+
+        #[test]
+        fn prop_page(bitmap in crate::strategies::any(), offset in 0..70_000_usize, limit in 0..100_usize) {
+            let expected: Vec<u16> = bitmap.iter().skip(offset).take(limit).collect();
+            assert_eq!(bitmap.page(offset, limit), expected);
+        }
+    }
+}
@@ -0,0 +1,108 @@
+//! The bitmaps are serialized as bytes containing their raw encoding, see [`Bitmap::to_bytes`].
+//!
+//! A [`BitmapView`] can be deserialized from a format able to lend its bytes,
+//! like bincode or postcard reading from a memory mapped file, without copying
+//! the payload.
+
+use core::fmt;
+
+use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use crate::{Bitmap, BitmapView};
+
+impl Serialize for Bitmap {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&self.to_bytes())
+    }
+}
+
+impl Serialize for BitmapView<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(self.as_bytes())
+    }
+}
+
+struct BitmapVisitor;
+
+impl<'de> Visitor<'de> for BitmapVisitor {
+    type Value = Bitmap;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} bytes", Bitmap::RAW_SIZE)
+    }
+
+    fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<Bitmap, E> {
+        Bitmap::from_bytes(bytes).map_err(|_| E::invalid_length(bytes.len(), &self))
+    }
+
+    /// The formats without a bytes type, like JSON, serialize them as a sequence.
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Bitmap, A::Error> {
+        let mut bytes = Vec::with_capacity(Bitmap::RAW_SIZE);
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        self.visit_bytes(&bytes)
+    }
+}
+
+impl<'de> Deserialize<'de> for Bitmap {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(BitmapVisitor)
+    }
+}
+
+struct BitmapViewVisitor;
+
+impl<'de> Visitor<'de> for BitmapViewVisitor {
+    type Value = BitmapView<'de>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} borrowed bytes", Bitmap::RAW_SIZE)
+    }
+
+    fn visit_borrowed_bytes<E: de::Error>(self, bytes: &'de [u8]) -> Result<Self::Value, E> {
+        BitmapView::new(bytes).map_err(|_| E::invalid_length(bytes.len(), &self))
+    }
+}
+
+/// Only the formats lending their bytes are supported, the other ones return
+/// an error since the view can't own its bytes.
+impl<'de: 'a, 'a> Deserialize<'de> for BitmapView<'a> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_bytes(BitmapViewVisitor)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use serde::de::value::{BorrowedBytesDeserializer, Error};
+    use serde_test::{assert_de_tokens_error, assert_ser_tokens, assert_tokens, Token};
+
+    use super::*;
+
+    #[test]
+    fn serde() {
+        let bitmap = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        let bytes: &'static [u8] = bitmap.to_bytes().leak();
+        assert_tokens(&bitmap, &[Token::Bytes(bytes)]);
+        assert_ser_tokens(&BitmapView::new(bytes).unwrap(), &[Token::Bytes(bytes)]);
+
+        let deserializer = BorrowedBytesDeserializer::<Error>::new(bytes);
+        let view = BitmapView::deserialize(deserializer).unwrap();
+        assert_eq!(view.as_bytes().as_ptr(), bytes.as_ptr());
+        assert!(view == bitmap);
+    }
+
+    #[test]
+    fn errors() {
+        assert_de_tokens_error::<Bitmap>(
+            &[Token::Bytes(&[0; 3])],
+            "invalid length 3, expected 8192 bytes",
+        );
+        assert_de_tokens_error::<BitmapView>(
+            &[Token::Bytes(&[0; Bitmap::RAW_SIZE])],
+            "invalid type: byte array, expected 8192 borrowed bytes",
+        );
+    }
+}
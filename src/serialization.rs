@@ -0,0 +1,253 @@
+use std::io::{self, Read, Write};
+
+use crate::{Bitmap, Error, Word};
+
+impl Bitmap {
+    /// Size in bytes of the raw encoding of a bitmap.
+    pub const RAW_SIZE: usize = Self::BITMAP_SIZE * core::mem::size_of::<Word>();
+
+    /// Encode the bitmap as every word of its store in little-endian order.
+    /// The length isn't stored since it can be recomputed from the words.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(Self::RAW_SIZE);
+        for word in self.store {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a bitmap previously encoded with [`Bitmap::to_bytes`].
+    /// Fails if `bytes` isn't exactly [`Bitmap::RAW_SIZE`] long.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.len() != Self::RAW_SIZE {
+            return Err(Error::InvalidLength {
+                expected: Self::RAW_SIZE,
+                found: bytes.len(),
+            });
+        }
+        let mut bitmap = Bitmap::new();
+        let mut count = 0;
+        for (word, chunk) in bitmap
+            .store
+            .iter_mut()
+            .zip(bytes.chunks_exact(core::mem::size_of::<Word>()))
+        {
+            *word = Word::from_le_bytes(chunk.try_into().unwrap());
+            count += word.count_ones();
+        }
+        bitmap.len = count as usize;
+        bitmap.debug_assert_invariants();
+        Ok(bitmap)
+    }
+
+    /// Write the raw encoding of the bitmap (see [`Bitmap::to_bytes`]) into `writer`.
+    /// The encoding has a fixed size so it doesn't need any length prefix; the
+    /// same writer can be used to write multiple bitmaps one after the other.
+    pub fn write_to(&self, mut writer: impl Write) -> io::Result<()> {
+        let mut buffer = [0; STREAM_CHUNK * core::mem::size_of::<Word>()];
+        for words in self.store.chunks(STREAM_CHUNK) {
+            for (word, bytes) in words
+                .iter()
+                .zip(buffer.chunks_exact_mut(core::mem::size_of::<Word>()))
+            {
+                bytes.copy_from_slice(&word.to_le_bytes());
+            }
+            writer.write_all(&buffer)?;
+        }
+        Ok(())
+    }
+
+    /// Read a bitmap written with [`Bitmap::write_to`] from `reader`.
+    /// Exactly [`Bitmap::RAW_SIZE`] bytes are consumed from the reader.
+    pub fn read_from(mut reader: impl Read) -> io::Result<Self> {
+        let mut bitmap = Bitmap::new();
+        let mut buffer = [0; STREAM_CHUNK * core::mem::size_of::<Word>()];
+        let mut count = 0;
+        for words in bitmap.store.chunks_mut(STREAM_CHUNK) {
+            reader.read_exact(&mut buffer)?;
+            for (word, bytes) in words
+                .iter_mut()
+                .zip(buffer.chunks_exact(core::mem::size_of::<Word>()))
+            {
+                *word = Word::from_le_bytes(bytes.try_into().unwrap());
+                count += word.count_ones();
+            }
+        }
+        bitmap.len = count as usize;
+        bitmap.debug_assert_invariants();
+        Ok(bitmap)
+    }
+
+    /// Encode the bitmap like [`Bitmap::to_bytes`] but only from its first to
+    /// its last non-empty word, prefixed by the positions of these two words
+    /// as little-endian `u16`. The word `i` is thus stored at the offset
+    /// `4 + (i - first_word) * 8`. An empty bitmap is encoded as no bytes at all.
+    pub fn to_truncated_bytes(&self) -> Vec<u8> {
+        let Some(first) = self.store.iter().position(|word| *word != 0) else {
+            return Vec::new();
+        };
+        let last = self.store.iter().rposition(|word| *word != 0).unwrap();
+        let words = &self.store[first..=last];
+        let mut bytes = Vec::with_capacity(TRUNCATED_HEADER_SIZE + core::mem::size_of_val(words));
+        bytes.extend_from_slice(&(first as u16).to_le_bytes());
+        bytes.extend_from_slice(&(last as u16).to_le_bytes());
+        for word in words {
+            bytes.extend_from_slice(&word.to_le_bytes());
+        }
+        bytes
+    }
+
+    /// Decode a bitmap previously encoded with [`Bitmap::to_truncated_bytes`].
+    /// Fails if the bounds of the words are invalid or don't match the length of `bytes`.
+    pub fn from_truncated_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        if bytes.is_empty() {
+            return Ok(Bitmap::new());
+        }
+        let Some((header, words)) = bytes.split_first_chunk::<TRUNCATED_HEADER_SIZE>() else {
+            return Err(Error::InvalidLength {
+                expected: TRUNCATED_HEADER_SIZE,
+                found: bytes.len(),
+            });
+        };
+        let first = u16::from_le_bytes([header[0], header[1]]) as usize;
+        let last = u16::from_le_bytes([header[2], header[3]]) as usize;
+        if first > last || last >= Self::BITMAP_SIZE {
+            return Err(Error::Corrupted("the bounds of the words are invalid"));
+        }
+        let expected = TRUNCATED_HEADER_SIZE + (last - first + 1) * core::mem::size_of::<Word>();
+        if bytes.len() != expected {
+            return Err(Error::InvalidLength {
+                expected,
+                found: bytes.len(),
+            });
+        }
+
+        let mut bitmap = Bitmap::new();
+        let mut count = 0;
+        for (word, chunk) in bitmap.store[first..=last]
+            .iter_mut()
+            .zip(words.chunks_exact(core::mem::size_of::<Word>()))
+        {
+            *word = Word::from_le_bytes(chunk.try_into().unwrap());
+            count += word.count_ones();
+        }
+        bitmap.len = count as usize;
+        bitmap.debug_assert_invariants();
+        Ok(bitmap)
+    }
+}
+
+/// Size in bytes of the bounds prefixing the truncated encoding.
+const TRUNCATED_HEADER_SIZE: usize = 4;
+
+/// Number of words going through the stack buffer at once while streaming.
+const STREAM_CHUNK: usize = 64;
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn roundtrip() {
+        let bitmap = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        let bytes = bitmap.to_bytes();
+        assert_eq!(bytes.len(), Bitmap::RAW_SIZE);
+        insta::assert_debug_snapshot!(&bytes[..9], @r###"
+        [
+            3,
+            0,
+            0,
+            0,
+            0,
+            0,
+            0,
+            128,
+            1,
+        ]
+        "###);
+
+        let decoded = Bitmap::from_bytes(&bytes).unwrap();
+        assert_eq!(decoded.len(), 6);
+        assert_eq!(decoded, bitmap);
+    }
+
+    #[test]
+    fn stream() {
+        let first = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        let second = Bitmap::from_iter(2000..3000);
+
+        let mut buffer = Vec::new();
+        first.write_to(&mut buffer).unwrap();
+        second.write_to(&mut buffer).unwrap();
+        assert_eq!(buffer.len(), 2 * Bitmap::RAW_SIZE);
+        assert_eq!(&buffer[..Bitmap::RAW_SIZE], first.to_bytes());
+
+        let mut reader = buffer.as_slice();
+        assert_eq!(Bitmap::read_from(&mut reader).unwrap(), first);
+        assert_eq!(Bitmap::read_from(&mut reader).unwrap(), second);
+        let err = Bitmap::read_from(&mut reader).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn bad_length() {
+        assert_eq!(
+            Bitmap::from_bytes(&[]),
+            Err(Error::InvalidLength {
+                expected: Bitmap::RAW_SIZE,
+                found: 0
+            })
+        );
+        assert!(Bitmap::from_bytes(&[0; Bitmap::RAW_SIZE - 1]).is_err());
+        assert!(Bitmap::from_bytes(&[0; Bitmap::RAW_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn truncated() {
+        assert!(Bitmap::new().to_truncated_bytes().is_empty());
+        assert_eq!(Bitmap::from_truncated_bytes(&[]).unwrap(), Bitmap::new());
+
+        let bitmap = Bitmap::from_iter([1000, 1001, 1100]);
+        let bytes = bitmap.to_truncated_bytes();
+        assert_eq!(bytes.len(), 4 + 3 * 8);
+        assert_eq!(&bytes[..4], [15, 0, 17, 0]);
+        assert_eq!(Bitmap::from_truncated_bytes(&bytes).unwrap(), bitmap);
+
+        let full = Bitmap::full().to_truncated_bytes();
+        assert_eq!(full.len(), 4 + Bitmap::RAW_SIZE);
+        assert_eq!(Bitmap::from_truncated_bytes(&full).unwrap(), Bitmap::full());
+
+        assert_eq!(
+            Bitmap::from_truncated_bytes(&bytes[..3]),
+            Err(Error::InvalidLength {
+                expected: 4,
+                found: 3
+            })
+        );
+        assert_eq!(
+            Bitmap::from_truncated_bytes(&bytes[..bytes.len() - 1]),
+            Err(Error::InvalidLength {
+                expected: 28,
+                found: 27
+            })
+        );
+        assert_eq!(
+            Bitmap::from_truncated_bytes(&[2, 0, 1, 0]),
+            Err(Error::Corrupted("the bounds of the words are invalid"))
+        );
+        assert_eq!(
+            Bitmap::from_truncated_bytes(&[0, 0, 0, 4]),
+            Err(Error::Corrupted("the bounds of the words are invalid"))
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn prop_truncated(bitmap in crate::strategies::any()) {
+            let bytes = bitmap.to_truncated_bytes();
+            assert!(bytes.len() <= 4 + Bitmap::RAW_SIZE);
+            assert_eq!(Bitmap::from_truncated_bytes(&bytes).unwrap(), bitmap);
+        }
+    }
+}
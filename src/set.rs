@@ -0,0 +1,191 @@
+//! The methods named after the ones of [`BTreeSet`](std::collections::BTreeSet),
+//! so code written against the std sets can switch to a bitmap with minimal edits.
+//!
+//! The set operations are the exception: [`Bitmap::intersection`] works in
+//! place, the lazy iterators of `BTreeSet::intersection`, `union`,
+//! `difference` and `symmetric_difference` are the `*_iter` methods, like
+//! [`Bitmap::intersection_iter`].
+
+use core::ops::RangeBounds;
+
+use crate::{Bitmap, Word};
+
+impl Bitmap {
+    /// Returns the smallest value of the bitmap.
+    #[inline]
+    pub fn first(&self) -> Option<u16> {
+        let key = self.store.iter().position(|word| *word != 0)?;
+        Some((key * Word::BITS as usize + self.store[key].trailing_zeros() as usize) as u16)
+    }
+
+    /// Returns the greatest value of the bitmap.
+    #[inline]
+    pub fn last(&self) -> Option<u16> {
+        let key = self.store.iter().rposition(|word| *word != 0)?;
+        let bit = Word::BITS - 1 - self.store[key].leading_zeros();
+        Some((key * Word::BITS as usize + bit as usize) as u16)
+    }
+
+    /// Removes and returns the smallest value of the bitmap.
+    #[inline]
+    pub fn pop_first(&mut self) -> Option<u16> {
+        let first = self.first()?;
+        self.remove(first);
+        Some(first)
+    }
+
+    /// Removes and returns the greatest value of the bitmap.
+    #[inline]
+    pub fn pop_last(&mut self) -> Option<u16> {
+        let last = self.last()?;
+        self.remove(last);
+        Some(last)
+    }
+
+    /// Iterates over the values contained in `range` in increasing order, same
+    /// as [`Bitmap::iter_range`].
+    #[inline]
+    pub fn range(&self, range: impl RangeBounds<u16>) -> impl Iterator<Item = u16> + '_ {
+        self.iter_range(range)
+    }
+
+    /// Returns `true` if all the values of `self` are in `other`.
+    #[inline]
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.len <= other.len
+            && self
+                .store
+                .iter()
+                .zip(&other.store)
+                .all(|(left, right)| left & !right == 0)
+    }
+
+    /// Returns `true` if all the values of `other` are in `self`.
+    #[inline]
+    pub fn is_superset(&self, other: &Self) -> bool {
+        other.is_subset(self)
+    }
+
+    /// Returns `true` if `self` and `other` don't have any value in common.
+    #[inline]
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.store
+            .iter()
+            .zip(&other.store)
+            .all(|(left, right)| left & right == 0)
+    }
+
+    /// Removes all the values.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.store = [0; Self::BITMAP_SIZE];
+        self.len = 0;
+        self.generation.bump();
+    }
+
+    /// Keeps only the values for which `f` returns `true`, in increasing order.
+    pub fn retain(&mut self, mut f: impl FnMut(u16) -> bool) {
+        let mut removed = 0;
+        for (key, word) in self.store.iter_mut().enumerate() {
+            let base = key * Word::BITS as usize;
+            let mut remaining = *word;
+            while remaining != 0 {
+                let bit = remaining.trailing_zeros();
+                if !f((base + bit as usize) as u16) {
+                    *word &= !(1 << bit);
+                    removed += 1;
+                }
+                // clear the lowest bit set
+                remaining &= remaining - 1;
+            }
+        }
+        self.len -= removed;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+
+    /// Moves all the values greater or equal to `value` in a new bitmap.
+    pub fn split_off(&mut self, value: u16) -> Bitmap {
+        self.extract_range(value..)
+    }
+}
+
+impl Extend<u16> for Bitmap {
+    fn extend<T: IntoIterator<Item = u16>>(&mut self, iter: T) {
+        iter.into_iter().for_each(|value| {
+            self.insert(value);
+        });
+    }
+}
+
+impl<'a> Extend<&'a u16> for Bitmap {
+    fn extend<T: IntoIterator<Item = &'a u16>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().copied());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn set() {
+        let mut bitmap = Bitmap::from_iter([3, 64, 1000, u16::MAX]);
+        assert_eq!(bitmap.first(), Some(3));
+        assert_eq!(bitmap.last(), Some(u16::MAX));
+        assert_eq!(bitmap.range(4..=1000).collect::<Vec<_>>(), [64, 1000]);
+        assert_eq!(bitmap.pop_first(), Some(3));
+        assert_eq!(bitmap.pop_last(), Some(u16::MAX));
+        assert_eq!(bitmap.to_vec(), [64, 1000]);
+
+        let high = bitmap.split_off(1000);
+        assert_eq!(bitmap.to_vec(), [64]);
+        assert_eq!(high.to_vec(), [1000]);
+        assert!(bitmap.is_disjoint(&high));
+        assert!(bitmap.is_subset(&Bitmap::full()));
+        assert!(Bitmap::full().is_superset(&high));
+        assert!(!high.is_superset(&bitmap));
+
+        bitmap.extend([1, 2, 2]);
+        bitmap.extend(&[5]);
+        assert_eq!(bitmap.to_vec(), [1, 2, 5, 64]);
+        bitmap.retain(|value| value % 2 == 0);
+        assert_eq!(bitmap.to_vec(), [2, 64]);
+        assert_eq!(bitmap.len(), 2);
+        bitmap.clear();
+        assert!(bitmap.is_empty());
+        assert_eq!(bitmap.first(), None);
+        assert_eq!(bitmap.last(), None);
+        assert_eq!(bitmap.pop_first(), None);
+        assert_eq!(bitmap.pop_last(), None);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_btreeset(left in crate::strategies::any(), right in crate::strategies::any(), split in any::<u16>()) {
+            let set: BTreeSet<u16> = left.iter().collect();
+            let other: BTreeSet<u16> = right.iter().collect();
+            assert_eq!(left.first(), set.first().copied());
+            assert_eq!(left.last(), set.last().copied());
+            assert_eq!(left.is_subset(&right), set.is_subset(&other));
+            assert_eq!(left.is_superset(&right), set.is_superset(&other));
+            assert_eq!(left.is_disjoint(&right), set.is_disjoint(&other));
+            assert!(left.range(..split).eq(set.range(..split).copied()));
+
+            let (mut left, mut set) = (left, set);
+            let high = left.split_off(split);
+            let high_set = set.split_off(&split);
+            assert!(left.iter().eq(set.iter().copied()));
+            assert!(high.iter().eq(high_set.iter().copied()));
+
+            let (mut right, mut other) = (right, other);
+            right.retain(|value| value % 3 != 0);
+            other.retain(|value| value % 3 != 0);
+            assert!(right.iter().eq(other.iter().copied()));
+            assert_eq!(right.len(), other.len());
+        }
+    }
+}
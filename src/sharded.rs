@@ -0,0 +1,216 @@
+use std::sync::{Mutex, MutexGuard};
+
+use crate::{Bitmap, Word};
+
+/// A bitmap that can be updated concurrently from multiple threads.
+///
+/// The words of the store are interleaved across `N` shards (word `i` belongs
+/// to the shard `i % N`), each behind its own lock. Thus, threads inserting
+/// values that are close to each other still end up on different locks
+/// as soon as the values are more than 64 apart.
+pub struct ShardedBitmap {
+    shards: Box<[Mutex<Shard>]>,
+}
+
+struct Shard {
+    len: usize,
+    words: Vec<Word>,
+}
+
+impl ShardedBitmap {
+    /// Creates an empty bitmap split in `shards` shards.
+    ///
+    /// # Panics
+    ///
+    /// If `shards` is `0` or greater than the number of words in a bitmap (1024).
+    pub fn new(shards: usize) -> Self {
+        assert!(
+            (1..=Bitmap::BITMAP_SIZE).contains(&shards),
+            "the number of shards must be between 1 and {}, got {shards}",
+            Bitmap::BITMAP_SIZE
+        );
+        let shards = (0..shards)
+            .map(|shard| {
+                let words = (shard..Bitmap::BITMAP_SIZE).step_by(shards).count();
+                Mutex::new(Shard {
+                    len: 0,
+                    words: vec![0; words],
+                })
+            })
+            .collect();
+        ShardedBitmap { shards }
+    }
+
+    #[inline]
+    pub fn shards(&self) -> usize {
+        self.shards.len()
+    }
+
+    /// Returns the shard and the position in the shard of the word containing `value`.
+    #[inline]
+    fn locate(&self, value: u16) -> (MutexGuard<'_, Shard>, usize, usize) {
+        let key = Bitmap::key(value);
+        let shard = self.shards[key % self.shards.len()].lock().unwrap();
+        (shard, key / self.shards.len(), Bitmap::bit_offset(value))
+    }
+
+    /// Returns `true` if the value was not already present in the bitmap.
+    #[inline]
+    pub fn insert(&self, value: u16) -> bool {
+        let (mut shard, key, bit) = self.locate(value);
+        let old_w = shard.words[key];
+        let new_w = old_w | 1 << bit;
+        let inserted = (old_w ^ new_w) >> bit;
+        shard.words[key] = new_w;
+        shard.len += inserted as usize;
+        inserted != 0
+    }
+
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn remove(&self, value: u16) -> bool {
+        let (mut shard, key, bit) = self.locate(value);
+        let old_w = shard.words[key];
+        let new_w = old_w & !(1 << bit);
+        let removed = (old_w ^ new_w) >> bit;
+        shard.words[key] = new_w;
+        shard.len -= removed as usize;
+        removed != 0
+    }
+
+    /// Returns `true` if the value was present in the bitmap.
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        let (shard, key, bit) = self.locate(value);
+        shard.words[key] & (1 << bit) != 0
+    }
+
+    /// Number of values in the bitmap. The shards are read one after the other,
+    /// thus the result can be outdated if other threads are updating the bitmap.
+    pub fn len(&self) -> usize {
+        self.shards
+            .iter()
+            .map(|shard| shard.lock().unwrap().len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Insert all the values of `other` in the bitmap.
+    pub fn merge(&self, other: &Bitmap) {
+        let n = self.shards.len();
+        for (i, shard) in self.shards.iter().enumerate() {
+            let mut shard = shard.lock().unwrap();
+            let shard = &mut *shard;
+            let mut count = 0;
+            for (word, other) in shard
+                .words
+                .iter_mut()
+                .zip(other.store[i..].iter().step_by(n))
+            {
+                *word |= other;
+                count += word.count_ones();
+            }
+            shard.len = count as usize;
+        }
+    }
+
+    /// Returns a consistent copy of the bitmap.
+    /// All the shards are locked while the copy is being made.
+    pub fn snapshot(&self) -> Bitmap {
+        let n = self.shards.len();
+        // always lock the shards in the same order to avoid any dead lock
+        let shards: Vec<_> = self.shards.iter().map(|s| s.lock().unwrap()).collect();
+        let mut bitmap = Bitmap::new();
+        for (i, shard) in shards.iter().enumerate() {
+            for (word, shard_word) in bitmap.store[i..].iter_mut().step_by(n).zip(&shard.words) {
+                *word = *shard_word;
+            }
+            bitmap.len += shard.len;
+        }
+        bitmap.debug_assert_invariants();
+        bitmap
+    }
+}
+
+impl From<&Bitmap> for ShardedBitmap {
+    /// Creates a sharded bitmap with one shard per available CPU.
+    fn from(bitmap: &Bitmap) -> Self {
+        let shards = std::thread::available_parallelism()
+            .map_or(1, |n| n.get())
+            .min(Bitmap::BITMAP_SIZE);
+        let sharded = ShardedBitmap::new(shards);
+        sharded.merge(bitmap);
+        sharded
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn insert_remove() {
+        let bitmap = ShardedBitmap::new(3);
+        assert!(bitmap.insert(0));
+        assert!(!bitmap.insert(0));
+        assert!(bitmap.insert(64));
+        assert!(bitmap.insert(128));
+        assert!(bitmap.insert(u16::MAX));
+        assert_eq!(bitmap.len(), 4);
+        assert!(bitmap.contains(64));
+        assert!(bitmap.remove(64));
+        assert!(!bitmap.remove(64));
+        assert!(!bitmap.contains(64));
+
+        insta::assert_debug_snapshot!(bitmap.snapshot(), @r###"
+        {
+            0,
+            128,
+            65535,
+        }
+        "###);
+    }
+
+    #[test]
+    #[should_panic]
+    fn too_many_shards() {
+        ShardedBitmap::new(Bitmap::BITMAP_SIZE + 1);
+    }
+
+    #[test]
+    fn concurrent_insert() {
+        let bitmap = ShardedBitmap::new(8);
+        std::thread::scope(|s| {
+            for thread in 0..4 {
+                let bitmap = &bitmap;
+                s.spawn(move || {
+                    for value in (thread..=u16::MAX).step_by(4) {
+                        bitmap.insert(value);
+                    }
+                });
+            }
+        });
+        assert_eq!(bitmap.len(), u16::MAX as usize + 1);
+        assert_eq!(bitmap.snapshot(), Bitmap::full());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_merge_snapshot(values in prop::collection::vec(0..=u16::MAX, 1..150), other in prop::collection::vec(0..=u16::MAX, 1..150), shards in 1..=Bitmap::BITMAP_SIZE) {
+            let sharded = ShardedBitmap::new(shards);
+            for value in &values {
+                sharded.insert(*value);
+            }
+            let other = Bitmap::from_iter(&other);
+            sharded.merge(&other);
+
+            let expected = Bitmap::from_iter(&values) | other;
+            assert_eq!(sharded.len(), expected.len());
+            assert_eq!(sharded.snapshot(), expected);
+        }
+    }
+}
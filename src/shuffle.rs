@@ -0,0 +1,155 @@
+use crate::select::select_in_word;
+use crate::{Bitmap, Word};
+
+/// Iterates over the values of a bitmap in a pseudo-random order, see [`Bitmap::iter_shuffled`].
+#[derive(Clone)]
+pub struct Shuffled<'a> {
+    bitmap: &'a Bitmap,
+    /// `cumulative[i]` is the number of values in the words before the word `i`.
+    cumulative: Vec<u32>,
+    /// Number of values already yielded, it's also the next index to permute.
+    yielded: usize,
+    /// The permutation works on indexes of `2 * half_bits` bits.
+    half_bits: u32,
+    seed: u64,
+}
+
+impl Bitmap {
+    /// Iterates over the values of the bitmap in a pseudo-random order
+    /// determined by `seed`: the same bitmap and seed always yield the same order.
+    ///
+    /// The indexes `0..len` are shuffled with a bijective permutation (a
+    /// Feistel network), then every index is mapped to its value with a
+    /// select. Nothing is collected or allocated besides a small table of
+    /// 1025 counters.
+    pub fn iter_shuffled(&self, seed: u64) -> Shuffled<'_> {
+        let mut cumulative = Vec::with_capacity(Self::BITMAP_SIZE + 1);
+        let mut count = 0;
+        cumulative.push(count);
+        for word in self.store {
+            count += word.count_ones();
+            cumulative.push(count);
+        }
+        // the smallest even number of bits able to represent all the indexes
+        let bits = usize::BITS - self.len.saturating_sub(1).leading_zeros();
+        Shuffled {
+            bitmap: self,
+            cumulative,
+            yielded: 0,
+            half_bits: bits.div_ceil(2).max(1),
+            seed,
+        }
+    }
+}
+
+/// splitmix64 finalizer.
+#[inline]
+fn mix(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+impl Shuffled<'_> {
+    const ROUNDS: u64 = 4;
+
+    /// A bijection over `0..2^(2 * half_bits)`.
+    #[inline]
+    fn permute(&self, index: u64) -> u64 {
+        let mask = (1 << self.half_bits) - 1;
+        let (mut left, mut right) = (index >> self.half_bits, index & mask);
+        for round in 0..Self::ROUNDS {
+            let f = mix(right ^ mix(self.seed.wrapping_add(round))) & mask;
+            (left, right) = (right, left ^ f);
+        }
+        (left << self.half_bits) | right
+    }
+}
+
+impl Iterator for Shuffled<'_> {
+    type Item = u16;
+
+    fn next(&mut self) -> Option<u16> {
+        let len = self.bitmap.len();
+        if self.yielded >= len {
+            return None;
+        }
+        // the permutation covers a power of two that can be bigger than the
+        // number of values, thus we walk the cycle until we get back in range
+        let mut index = self.yielded as u64;
+        loop {
+            index = self.permute(index);
+            if index < len as u64 {
+                break;
+            }
+        }
+        self.yielded += 1;
+
+        let index = index as u32;
+        let key = self.cumulative.partition_point(|count| *count <= index) - 1;
+        let bit = select_in_word(self.bitmap.store[key], index - self.cumulative[key]);
+        Some((key * Word::BITS as usize + bit as usize) as u16)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.bitmap.len() - self.yielded;
+        (remaining, Some(remaining))
+    }
+}
+
+impl ExactSizeIterator for Shuffled<'_> {}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn shuffled() {
+        let bitmap = Bitmap::from_iter((0..10).chain([1000, u16::MAX]));
+        let shuffled: Vec<u16> = bitmap.iter_shuffled(42).collect();
+        insta::assert_debug_snapshot!(shuffled, @r###"
+        [
+            7,
+            2,
+            9,
+            1,
+            3,
+            6,
+            65535,
+            8,
+            5,
+            1000,
+            4,
+            0,
+        ]
+        "###);
+        assert_eq!(shuffled, bitmap.iter_shuffled(42).collect::<Vec<_>>());
+        assert_ne!(shuffled, bitmap.iter_shuffled(43).collect::<Vec<_>>());
+
+        assert_eq!(Bitmap::new().iter_shuffled(0).next(), None);
+        assert_eq!(
+            Bitmap::from_iter([7]).iter_shuffled(0).collect::<Vec<_>>(),
+            [7]
+        );
+    }
+
+    #[test]
+    fn full() {
+        let mut values: Vec<u16> = Bitmap::full().iter_shuffled(3).collect();
+        assert_ne!(values[..10], [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]);
+        values.sort_unstable();
+        assert!(values.into_iter().eq(0..=u16::MAX));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_permutation(bitmap in crate::strategies::any(), seed in any::<u64>()) {
+            let shuffled = bitmap.iter_shuffled(seed);
+            assert_eq!(shuffled.len(), bitmap.len());
+            let mut values: Vec<u16> = shuffled.collect();
+            values.sort_unstable();
+            assert_eq!(values, bitmap.to_vec());
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use core::fmt;
+
+use crate::{Bitmap, Word};
+
+/// A bitmap folded on a few hundred bits: the value `v` sets the bit
+/// `v % bits`. Two bitmaps can't intersect if their sketches don't, thus
+/// [`SmallSketch::may_intersect`] rejects most of the candidate pairs of
+/// sparse bitmaps before running the full intersections.
+#[derive(Clone, PartialEq, Eq)]
+pub struct SmallSketch {
+    words: Box<[Word]>,
+}
+
+impl SmallSketch {
+    /// Number of bits of the sketch.
+    #[inline]
+    pub fn bits(&self) -> usize {
+        self.words.len() * Word::BITS as usize
+    }
+
+    /// Returns `false` if `value` is definitely absent from the bitmap.
+    #[inline]
+    pub fn may_contain(&self, value: u16) -> bool {
+        let bit = value as usize % self.bits();
+        self.words[bit / Word::BITS as usize] & (1 << (bit % Word::BITS as usize)) != 0
+    }
+
+    /// Returns `false` if the bitmaps of both sketches definitely don't have
+    /// any value in common.
+    ///
+    /// # Panics
+    ///
+    /// If the sketches don't have the same number of bits.
+    #[inline]
+    pub fn may_intersect(&self, other: &Self) -> bool {
+        assert_eq!(
+            self.bits(),
+            other.bits(),
+            "can't compare sketches of different sizes"
+        );
+        self.words
+            .iter()
+            .zip(other.words.iter())
+            .any(|(left, right)| left & right != 0)
+    }
+}
+
+impl fmt::Debug for SmallSketch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SmallSketch")
+            .field("bits", &self.bits())
+            .field(
+                "ones",
+                &self.words.iter().map(|word| word.count_ones()).sum::<u32>(),
+            )
+            .finish()
+    }
+}
+
+impl Bitmap {
+    /// Folds the bitmap in a sketch of `bits` bits, see [`SmallSketch`].
+    ///
+    /// # Panics
+    ///
+    /// If `bits` isn't a power of two between 64 and 65536.
+    pub fn sketch(&self, bits: usize) -> SmallSketch {
+        assert!(
+            bits.is_power_of_two() && (Word::BITS as usize..=u16::MAX as usize + 1).contains(&bits),
+            "the sketch size must be a power of two between 64 and 65536, got {bits}"
+        );
+        let mut words = vec![0; bits / Word::BITS as usize].into_boxed_slice();
+        for chunk in self.store.chunks_exact(words.len()) {
+            for (word, folded) in words.iter_mut().zip(chunk) {
+                *word |= folded;
+            }
+        }
+        SmallSketch { words }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn sketch() {
+        let left = Bitmap::from_iter([1, 300]).sketch(256);
+        let right = Bitmap::from_iter([2, 257]).sketch(256);
+        insta::assert_snapshot!(format!("{left:?}"), @"SmallSketch { bits: 256, ones: 2 }");
+        assert_eq!(left.bits(), 256);
+        // 257 is folded on 1
+        assert!(left.may_intersect(&right));
+        assert!(left.may_contain(44));
+        assert!(!left.may_contain(2));
+        assert!(!Bitmap::from_iter([2]).sketch(256).may_intersect(&left));
+        assert!(!Bitmap::new()
+            .sketch(64)
+            .may_intersect(&Bitmap::full().sketch(64)));
+        assert_eq!(Bitmap::full().sketch(65536), Bitmap::full().sketch(65536));
+    }
+
+    #[test]
+    #[should_panic = "the sketch size must be a power of two between 64 and 65536, got 32"]
+    fn sketch_too_small() {
+        Bitmap::new().sketch(32);
+    }
+
+    #[test]
+    #[should_panic = "can't compare sketches of different sizes"]
+    fn sketch_different_sizes() {
+        Bitmap::new()
+            .sketch(64)
+            .may_intersect(&Bitmap::new().sketch(128));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_sketch(left in crate::strategies::any(), right in crate::strategies::any(), shift in 6..=16_u32) {
+            let bits = 1 << shift;
+            let (left_sketch, right_sketch) = (left.sketch(bits), right.sketch(bits));
+            if left.intersection_iter(&right).next().is_some() {
+                assert!(left_sketch.may_intersect(&right_sketch));
+            }
+            if shift == 16 {
+                assert_eq!(left_sketch.may_intersect(&right_sketch), !left.is_disjoint(&right));
+            }
+            for value in left.iter() {
+                assert!(left_sketch.may_contain(value));
+            }
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use core::fmt;
+use core::ops::Range;
+
+use crate::{Bitmap, Iter, Word};
+
+/// A borrowed window over a word-aligned range of values of a bitmap,
+/// see [`Bitmap::slice`].
+#[derive(Clone, Copy)]
+pub struct BitmapSlice<'a> {
+    words: &'a [Word],
+    first_key: usize,
+}
+
+impl Bitmap {
+    /// Borrows the values of `range`. Both bounds must be multiples of 64
+    /// since a slice is made of whole words of the store.
+    ///
+    /// # Panics
+    ///
+    /// If the bounds are not aligned on 64, if the start is greater than the
+    /// end, or if the end is greater than 65536.
+    pub fn slice(&self, range: Range<usize>) -> BitmapSlice<'_> {
+        let bits = Word::BITS as usize;
+        assert!(
+            range.start.is_multiple_of(bits) && range.end.is_multiple_of(bits),
+            "the bounds of a slice must be multiples of {bits}, got {range:?}"
+        );
+        assert!(
+            range.start <= range.end && range.end <= Self::BITMAP_SIZE * bits,
+            "the bounds of a slice must be ordered and at most 65536, got {range:?}"
+        );
+        let keys = range.start / bits..range.end / bits;
+        BitmapSlice {
+            words: &self.store[keys.clone()],
+            first_key: keys.start,
+        }
+    }
+}
+
+impl<'a> BitmapSlice<'a> {
+    /// The range of values covered by the slice.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        let bits = Word::BITS as usize;
+        self.first_key * bits..(self.first_key + self.words.len()) * bits
+    }
+
+    /// Returns `true` if the value is in the slice, the values outside of the
+    /// range of the slice are never present.
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        Bitmap::key(value)
+            .checked_sub(self.first_key)
+            .and_then(|key| self.words.get(key))
+            .is_some_and(|word| word & (1 << Bitmap::bit_offset(value)) != 0)
+    }
+
+    /// Number of values in the slice.
+    pub fn len(&self) -> usize {
+        self.words
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words.iter().all(|word| *word == 0)
+    }
+
+    /// Iterates over the values of the slice in increasing order.
+    pub fn iter(&self) -> Iter<'a> {
+        Iter::new(self.words, self.first_key)
+    }
+
+    /// Copy the slice in a bitmap, the values outside of the slice are unset.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        bitmap.store[self.first_key..self.first_key + self.words.len()].copy_from_slice(self.words);
+        bitmap.len = self.len();
+        bitmap.debug_assert_invariants();
+        bitmap
+    }
+
+    /// Only the words of the slice are combined and counted.
+    fn combine(&self, other: &Self, f: impl Fn(Word, Word) -> Word) -> BitmapSliceBuf {
+        assert_eq!(
+            self.range(),
+            other.range(),
+            "the set operations require slices over the same range"
+        );
+        let mut len = 0;
+        let words = self
+            .words
+            .iter()
+            .zip(other.words)
+            .map(|(left, right)| {
+                let word = f(*left, *right);
+                len += word.count_ones() as usize;
+                word
+            })
+            .collect();
+        BitmapSliceBuf {
+            words,
+            first_key: self.first_key,
+            len,
+        }
+    }
+
+    /// Returns the values present in both slices.
+    ///
+    /// # Panics
+    ///
+    /// If the slices don't cover the same range of values, it's also the case of all
+    /// the other set operations.
+    pub fn intersection(&self, other: &Self) -> BitmapSliceBuf {
+        self.combine(other, |l, r| l & r)
+    }
+
+    /// Returns the values present in either slice.
+    pub fn union(&self, other: &Self) -> BitmapSliceBuf {
+        self.combine(other, |l, r| l | r)
+    }
+
+    /// Returns the values present in `self` but not in `other`.
+    pub fn difference(&self, other: &Self) -> BitmapSliceBuf {
+        self.combine(other, |l, r| l & !r)
+    }
+
+    /// Returns the number of values present in both slices without materializing them.
+    pub fn intersection_len(&self, other: &Self) -> usize {
+        assert_eq!(
+            self.range(),
+            other.range(),
+            "the set operations require slices over the same range"
+        );
+        self.words
+            .iter()
+            .zip(other.words)
+            .map(|(l, r)| (l & r).count_ones() as usize)
+            .sum()
+    }
+}
+
+/// The owned result of a set operation between two slices, made of the words
+/// of their range only.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BitmapSliceBuf {
+    words: Vec<Word>,
+    first_key: usize,
+    len: usize,
+}
+
+impl BitmapSliceBuf {
+    /// Borrows the result as a slice, to chain the set operations.
+    #[inline]
+    pub fn as_slice(&self) -> BitmapSlice<'_> {
+        BitmapSlice {
+            words: &self.words,
+            first_key: self.first_key,
+        }
+    }
+
+    /// The range of values covered by the result.
+    #[inline]
+    pub fn range(&self) -> Range<usize> {
+        self.as_slice().range()
+    }
+
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterates over the values of the result in increasing order.
+    pub fn iter(&self) -> Iter<'_> {
+        self.as_slice().iter()
+    }
+
+    /// Copy the result in a bitmap, the values outside of its range are unset.
+    pub fn to_bitmap(&self) -> Bitmap {
+        self.as_slice().to_bitmap()
+    }
+}
+
+impl fmt::Debug for BitmapSliceBuf {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn slice() {
+        let bitmap = Bitmap::from_iter([0, 63, 64, 100, 127, 128, u16::MAX]);
+        let slice = bitmap.slice(64..128);
+        assert_eq!(slice.range(), 64..128);
+        assert_eq!(slice.len(), 3);
+        assert!(slice.contains(100));
+        assert!(!slice.contains(63));
+        assert!(!slice.contains(128));
+        insta::assert_debug_snapshot!(slice.iter().collect::<Vec<_>>(), @r###"
+        [
+            64,
+            100,
+            127,
+        ]
+        "###);
+        insta::assert_debug_snapshot!(slice.to_bitmap(), @r###"
+        {
+            64,
+            100,
+            127,
+        }
+        "###);
+
+        let other = Bitmap::from_iter([65, 100, 200]);
+        let other = other.slice(64..128);
+        insta::assert_debug_snapshot!(slice.intersection(&other), @r###"
+        {
+            100,
+        }
+        "###);
+        assert_eq!(slice.intersection_len(&other), 1);
+        assert_eq!(slice.union(&other).len(), 4);
+        assert_eq!(slice.union(&other).range(), 64..128);
+        let difference = slice.difference(&other);
+        assert_eq!(difference.iter().collect::<Vec<_>>(), [64, 127]);
+        assert_eq!(difference.as_slice().intersection(&slice).len(), 2);
+
+        let full = bitmap.slice(0..65536);
+        assert_eq!(full.to_bitmap(), bitmap);
+        assert!(bitmap.slice(128..128).is_empty());
+    }
+
+    #[test]
+    #[should_panic = "the bounds of a slice must be multiples of 64, got 10..128"]
+    fn unaligned() {
+        Bitmap::new().slice(10..128);
+    }
+
+    #[test]
+    #[should_panic = "the bounds of a slice must be ordered and at most 65536, got 128..64"]
+    #[allow(clippy::reversed_empty_ranges)]
+    fn reversed() {
+        Bitmap::new().slice(128..64);
+    }
+
+    #[test]
+    #[should_panic = "the bounds of a slice must be ordered and at most 65536, got 0..65600"]
+    fn too_large() {
+        Bitmap::new().slice(0..65600);
+    }
+
+    #[test]
+    #[should_panic = "the set operations require slices over the same range"]
+    fn different_ranges() {
+        let bitmap = Bitmap::new();
+        bitmap.slice(0..64).union(&bitmap.slice(64..128));
+    }
+
+    proptest! {
+        #[test]
+        fn prop_slice(left in crate::strategies::any(), right in crate::strategies::any(), start in 0..1024_usize, len in 0..1024_usize) {
+            let range = start * 64..(start + len).min(1024) * 64;
+            let (l, r) = (left.slice(range.clone()), right.slice(range.clone()));
+            let in_range = |bitmap: Bitmap| Bitmap::from_iter(bitmap.iter().filter(|v| range.contains(&(*v as usize))));
+
+            assert_eq!(l.to_bitmap(), in_range(left.clone()));
+            assert_eq!(l.intersection(&r).to_bitmap(), in_range(left.clone() & &right));
+            assert_eq!(l.intersection(&r).len(), in_range(left.clone() & &right).len());
+            assert_eq!(l.intersection_len(&r), in_range(left.clone() & &right).len());
+            assert_eq!(l.union(&r).to_bitmap(), in_range(left.clone() | &right));
+            assert_eq!(l.difference(&r).to_bitmap(), in_range(left.clone() - &right));
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+use crate::Bitmap;
+
+/// Assigns arbitrary keys (typically `u32` or `u64` ids) to the `u16` slots
+/// of a bitmap. The slots of the removed keys are recycled, the smallest free
+/// slot is always used first to keep the used slots dense.
+#[derive(Clone)]
+pub struct SlotMap16<K> {
+    used: Bitmap,
+    /// All the slots lower than this one are used.
+    free_hint: u16,
+    slots: HashMap<K, u16>,
+    keys: Vec<Option<K>>,
+}
+
+impl<K: Hash + Eq + Clone> SlotMap16<K> {
+    pub fn new() -> Self {
+        SlotMap16 {
+            used: Bitmap::new(),
+            free_hint: 0,
+            slots: HashMap::new(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Returns the slot of `key`, a slot is assigned to the key if it didn't
+    /// have one. Returns `None` if the key is new and all the slots are used.
+    pub fn insert(&mut self, key: K) -> Option<u16> {
+        if let Some(slot) = self.slots.get(&key) {
+            return Some(*slot);
+        }
+        let slot = self.used.next_absent(self.free_hint)?;
+        self.used.insert(slot);
+        self.free_hint = slot;
+        if self.keys.len() <= slot as usize {
+            self.keys.resize(slot as usize + 1, None);
+        }
+        self.keys[slot as usize] = Some(key.clone());
+        self.slots.insert(key, slot);
+        Some(slot)
+    }
+
+    /// Frees the slot of `key` and returns it.
+    pub fn remove(&mut self, key: &K) -> Option<u16> {
+        let slot = self.slots.remove(key)?;
+        self.used.remove(slot);
+        self.free_hint = self.free_hint.min(slot);
+        self.keys[slot as usize] = None;
+        Some(slot)
+    }
+
+    /// Returns the slot assigned to `key`.
+    pub fn get(&self, key: &K) -> Option<u16> {
+        self.slots.get(key).copied()
+    }
+
+    /// Returns the key owning `slot`.
+    pub fn key(&self, slot: u16) -> Option<&K> {
+        self.keys.get(slot as usize)?.as_ref()
+    }
+
+    /// The slots currently in use.
+    pub fn slots(&self) -> &Bitmap {
+        &self.used
+    }
+
+    pub fn len(&self) -> usize {
+        self.used.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.used.is_empty()
+    }
+}
+
+impl<K: Hash + Eq + Clone> Default for SlotMap16<K> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn slots() {
+        let mut map = SlotMap16::new();
+        assert_eq!(map.insert(1_000_000_u64), Some(0));
+        assert_eq!(map.insert(42), Some(1));
+        assert_eq!(map.insert(7), Some(2));
+        assert_eq!(map.insert(42), Some(1));
+        assert_eq!(map.len(), 3);
+
+        assert_eq!(map.remove(&42), Some(1));
+        assert_eq!(map.remove(&42), None);
+        assert_eq!(map.get(&42), None);
+        assert_eq!(map.key(1), None);
+
+        // the freed slot is recycled
+        assert_eq!(map.insert(8), Some(1));
+        assert_eq!(map.key(1), Some(&8));
+        assert_eq!(map.get(&7), Some(2));
+        insta::assert_debug_snapshot!(map.slots(), @r###"
+        {
+            0,
+            1,
+            2,
+        }
+        "###);
+    }
+
+    #[test]
+    fn full() {
+        let mut map = SlotMap16::new();
+        for key in 0..=u16::MAX as u32 {
+            assert_eq!(map.insert(key), Some(key as u16));
+        }
+        assert_eq!(map.insert(70_000), None);
+        assert_eq!(map.remove(&12), Some(12));
+        assert_eq!(map.insert(70_000), Some(12));
+    }
+}
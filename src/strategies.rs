@@ -0,0 +1,82 @@
+//! [`proptest`] strategies generating bitmaps of various shapes, to property-test
+//! code working with bitmaps.
+//!
+//! ```
+//! use bitmap::strategies;
+//! use proptest::prelude::*;
+//!
+//! proptest!(|(left in strategies::any(), right in strategies::any())| {
+//!     assert_eq!(left.clone() | &right, right | &left);
+//! });
+//! ```
+
+use proptest::prelude::*;
+
+use crate::Bitmap;
+
+/// A few hundred values spread randomly over the whole universe.
+pub fn sparse() -> impl Strategy<Value = Bitmap> {
+    prop::collection::vec(0..=u16::MAX, 0..300).prop_map(Bitmap::from_iter)
+}
+
+/// A nearly full bitmap with a few hundred values missing.
+pub fn dense() -> impl Strategy<Value = Bitmap> {
+    prop::collection::vec(0..=u16::MAX, 0..300).prop_map(|missing| {
+        let mut bitmap = Bitmap::full();
+        for value in missing {
+            bitmap.remove(value);
+        }
+        bitmap
+    })
+}
+
+/// A bitmap made of a few runs of consecutive values of random lengths.
+pub fn runny() -> impl Strategy<Value = Bitmap> {
+    prop::collection::vec((0..=u16::MAX, 1..2000_u16), 0..20).prop_map(|runs| {
+        let mut bitmap = Bitmap::new();
+        for (start, len) in runs {
+            for value in start..=start.saturating_add(len - 1) {
+                bitmap.insert(value);
+            }
+        }
+        bitmap
+    })
+}
+
+/// The bitmaps that tend to break the implementations: empty, full, only the
+/// bounds of the universe or values sitting on the boundaries of the words.
+pub fn edge_cases() -> impl Strategy<Value = Bitmap> {
+    (0..8).prop_map(|case| match case {
+        0 => Bitmap::new(),
+        1 => Bitmap::full(),
+        2 => Bitmap::from_iter([0]),
+        3 => Bitmap::from_iter([u16::MAX]),
+        4 => Bitmap::from_iter([0, u16::MAX]),
+        5 => Bitmap::from_iter((0..=u16::MAX).step_by(64)),
+        6 => Bitmap::from_iter((63..=u16::MAX).step_by(64)),
+        _ => Bitmap::from_iter((0..=u16::MAX).step_by(2)),
+    })
+}
+
+/// Any of the previous shapes.
+pub fn any() -> impl Strategy<Value = Bitmap> {
+    prop_oneof![sparse(), dense(), runny(), edge_cases()]
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn shapes(sparse in sparse(), dense in dense(), runny in runny(), edge in edge_cases()) {
+            sparse.assert_invariants();
+            dense.assert_invariants();
+            runny.assert_invariants();
+            edge.assert_invariants();
+
+            assert!(sparse.len() <= 300);
+            assert!(dense.len() >= u16::MAX as usize + 1 - 300);
+        }
+    }
+}
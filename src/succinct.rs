@@ -0,0 +1,210 @@
+//! Implementations of the bit vector, rank and select traits of the
+//! [`succinct`] crate, so a bitmap can be used as the bit vector layer of the
+//! succinct data structures (wavelet trees, FM-indexes, ...).
+//!
+//! The bit vectors are always 65536 bits long, the bit `i` is set if the value
+//! `i` is in the bitmap. Beware that the rank of `succinct` counts the bits up
+//! to and *including* the position, unlike [`Bitmap::rank`].
+
+use succinct::rank::{BitRankSupport, RankSupport};
+use succinct::select::{Select0Support, Select1Support, SelectSupport};
+use succinct::{BitVec, BitVecMut};
+
+use crate::select::select_in_word;
+use crate::{Bitmap, RankedBitmap, Word};
+
+const BIT_LEN: u64 = u16::MAX as u64 + 1;
+
+#[track_caller]
+fn position(position: u64) -> u16 {
+    u16::try_from(position).unwrap_or_else(|_| panic!("the position {position} is out of bounds"))
+}
+
+impl BitVec for Bitmap {
+    type Block = Word;
+
+    fn bit_len(&self) -> u64 {
+        BIT_LEN
+    }
+
+    fn get_bit(&self, position: u64) -> bool {
+        self.contains(self::position(position))
+    }
+
+    fn get_block(&self, position: usize) -> Word {
+        self.store[position]
+    }
+}
+
+impl BitVecMut for Bitmap {
+    fn set_bit(&mut self, position: u64, value: bool) {
+        let position = self::position(position);
+        if value {
+            self.insert(position);
+        } else {
+            self.remove(position);
+        }
+    }
+
+    fn set_block(&mut self, position: usize, value: Word) {
+        self.len =
+            self.len - self.store[position].count_ones() as usize + value.count_ones() as usize;
+        self.store[position] = value;
+        self.generation.bump();
+        self.debug_assert_invariants();
+    }
+}
+
+impl RankSupport for Bitmap {
+    type Over = bool;
+
+    fn rank(&self, position: u64, value: bool) -> u64 {
+        if value {
+            self.rank1(position)
+        } else {
+            self.rank0(position)
+        }
+    }
+
+    fn limit(&self) -> u64 {
+        BIT_LEN
+    }
+}
+
+impl BitRankSupport for Bitmap {
+    fn rank1(&self, position: u64) -> u64 {
+        let position = self::position(position);
+        (self.rank(position) + self.contains(position) as usize) as u64
+    }
+}
+
+impl Select1Support for Bitmap {
+    fn select1(&self, index: u64) -> Option<u64> {
+        self.select(usize::try_from(index).ok()?).map(u64::from)
+    }
+}
+
+impl Select0Support for Bitmap {
+    fn select0(&self, index: u64) -> Option<u64> {
+        let mut remaining = index;
+        for (key, word) in self.store.iter().enumerate() {
+            let count = word.count_zeros() as u64;
+            if remaining < count {
+                let bit = select_in_word(!word, remaining as u32);
+                return Some(key as u64 * Word::BITS as u64 + bit as u64);
+            }
+            remaining -= count;
+        }
+        None
+    }
+}
+
+impl SelectSupport for Bitmap {
+    type Over = bool;
+
+    fn select(&self, index: u64, value: bool) -> Option<u64> {
+        if value {
+            self.select1(index)
+        } else {
+            self.select0(index)
+        }
+    }
+}
+
+/// The [`RankedBitmap`] answers the rank and select queries with its counters.
+impl BitVec for RankedBitmap {
+    type Block = Word;
+
+    fn bit_len(&self) -> u64 {
+        BIT_LEN
+    }
+
+    fn get_bit(&self, position: u64) -> bool {
+        self.contains(self::position(position))
+    }
+
+    fn get_block(&self, position: usize) -> Word {
+        self.bitmap().store[position]
+    }
+}
+
+impl RankSupport for RankedBitmap {
+    type Over = bool;
+
+    fn rank(&self, position: u64, value: bool) -> u64 {
+        if value {
+            self.rank1(position)
+        } else {
+            self.rank0(position)
+        }
+    }
+
+    fn limit(&self) -> u64 {
+        BIT_LEN
+    }
+}
+
+impl BitRankSupport for RankedBitmap {
+    fn rank1(&self, position: u64) -> u64 {
+        self.range_cardinality(..=self::position(position)) as u64
+    }
+}
+
+impl Select1Support for RankedBitmap {
+    fn select1(&self, index: u64) -> Option<u64> {
+        self.select(usize::try_from(index).ok()?).map(u64::from)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn succinct() {
+        let mut bitmap = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        assert_eq!(bitmap.bit_len(), 65536);
+        assert_eq!(bitmap.block_len(), 1024);
+        assert!(bitmap.get_bit(63));
+        assert_eq!(bitmap.get_block(1), 1);
+        assert_eq!(bitmap.rank1(0), 1);
+        assert_eq!(bitmap.rank1(62), 2);
+        assert_eq!(bitmap.rank0(62), 61);
+        assert_eq!(bitmap.rank1(u16::MAX as u64), 6);
+        assert_eq!(bitmap.select1(5), Some(u16::MAX as u64));
+        assert_eq!(bitmap.select1(6), None);
+        assert_eq!(bitmap.select0(0), Some(2));
+        assert_eq!(bitmap.select0(61), Some(65));
+        assert_eq!(Bitmap::full().select0(0), None);
+
+        bitmap.set_bit(2, true);
+        bitmap.set_block(1, 0b110);
+        bitmap.assert_invariants();
+        assert_eq!(bitmap.to_vec(), [0, 1, 2, 63, 65, 66, 1000, u16::MAX]);
+
+        let ranked = RankedBitmap::from(bitmap.clone());
+        assert_eq!(ranked.rank1(65), 5);
+        assert_eq!(ranked.select1(4), Some(65));
+    }
+
+    #[test]
+    #[should_panic = "the position 65536 is out of bounds"]
+    fn out_of_bounds() {
+        Bitmap::new().get_bit(65536);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_succinct(bitmap in crate::strategies::any(), position in any::<u16>(), index in 0..70_000_u64) {
+            let ranked = RankedBitmap::from(bitmap.clone());
+            let expected = bitmap.iter().filter(|value| *value <= position).count() as u64;
+            assert_eq!(BitRankSupport::rank1(&bitmap, position as u64), expected);
+            assert_eq!(BitRankSupport::rank1(&ranked, position as u64), expected);
+            assert_eq!(bitmap.select1(index), ranked.select1(index));
+
+            let complement = bitmap.complement_view();
+            assert_eq!(bitmap.select0(index), complement.iter().nth(index as usize).map(u64::from));
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! The kernels used on the targets without a popcount instruction, where
+//! [`u64::count_ones`] is emulated with a handful of bit operations for every
+//! single word.
+//!
+//! The SWAR (SIMD within a register) kernels only count the bits of every byte
+//! of a word, then sum these counters for a whole chunk of words before doing
+//! the expensive horizontal sum once per chunk.
+
+use crate::backend::{Backend, Op, Scalar};
+use crate::{Bitmap, Word};
+
+/// Whether [`u64::count_ones`] compiles to a single instruction. The wasm
+/// targets always have one.
+pub(crate) const HARDWARE_POPCOUNT: bool = cfg!(any(
+    target_feature = "popcnt",
+    target_feature = "zbb",
+    target_feature = "neon",
+    target_family = "wasm",
+));
+
+/// Number of words summed before the horizontal sum. The bytes of the
+/// accumulator never exceed `8 * CHUNK_WORDS` and must fit in a `u8`.
+const CHUNK_WORDS: usize = 8;
+
+const ONES: Word = Word::MAX / 0xFF;
+
+/// Returns the number of bits set in every byte of `word`, in the byte itself.
+#[inline(always)]
+fn byte_counts(word: Word) -> Word {
+    let word = word - ((word >> 1) & (ONES * 0x55));
+    let word = (word & (ONES * 0x33)) + ((word >> 2) & (ONES * 0x33));
+    (word + (word >> 4)) & (ONES * 0x0F)
+}
+
+/// Sums the bytes of `word`.
+#[inline(always)]
+fn horizontal_sum(word: Word) -> usize {
+    // the sum of the 8 bytes may not fit in a byte, sum them by pairs first
+    const LOW_BYTES: Word = 0x00FF_00FF_00FF_00FF;
+    let pairs = (word & LOW_BYTES) + ((word >> 8) & LOW_BYTES);
+    (pairs.wrapping_mul(0x0001_0001_0001_0001) >> 48) as usize
+}
+
+/// Returns the number of bits set in `words`.
+#[inline]
+pub(crate) fn count_ones(words: &[Word]) -> usize {
+    let (chunks, remainder) = words.as_chunks::<CHUNK_WORDS>();
+    let mut count = 0;
+    for chunk in chunks {
+        // two independent accumulators let the additions run in parallel
+        let (mut even, mut odd) = (0, 0);
+        for pair in chunk.chunks_exact(2) {
+            even += byte_counts(pair[0]);
+            odd += byte_counts(pair[1]);
+        }
+        count += horizontal_sum(even) + horizontal_sum(odd);
+    }
+    count
+        + remainder
+            .iter()
+            .map(|word| horizontal_sum(byte_counts(*word)))
+            .sum::<usize>()
+}
+
+/// The backend counting the bits of a whole chunk of words at once.
+#[derive(Clone, Copy)]
+pub(crate) struct Swar;
+
+impl Backend for Swar {
+    type Lane = [Word; CHUNK_WORDS];
+
+    const LANE_WORDS: usize = CHUNK_WORDS;
+
+    #[inline(always)]
+    fn load(self, words: &[Word]) -> Self::Lane {
+        words.try_into().unwrap()
+    }
+
+    #[inline(always)]
+    fn store(self, lane: Self::Lane, words: &mut [Word]) {
+        words.copy_from_slice(&lane);
+    }
+
+    #[inline(always)]
+    fn and(self, left: Self::Lane, right: Self::Lane) -> Self::Lane {
+        core::array::from_fn(|i| left[i] & right[i])
+    }
+
+    #[inline(always)]
+    fn or(self, left: Self::Lane, right: Self::Lane) -> Self::Lane {
+        core::array::from_fn(|i| left[i] | right[i])
+    }
+
+    #[inline(always)]
+    fn and_not(self, left: Self::Lane, right: Self::Lane) -> Self::Lane {
+        core::array::from_fn(|i| left[i] & !right[i])
+    }
+
+    #[inline(always)]
+    fn xor(self, left: Self::Lane, right: Self::Lane) -> Self::Lane {
+        core::array::from_fn(|i| left[i] ^ right[i])
+    }
+
+    #[inline(always)]
+    fn not(self, lane: Self::Lane) -> Self::Lane {
+        lane.map(|word| !word)
+    }
+
+    #[inline(always)]
+    fn count_ones(self, lane: Self::Lane) -> usize {
+        horizontal_sum(lane.into_iter().map(byte_counts).sum())
+    }
+}
+
+// the other kernels don't need any fallback
+#[cfg_attr(not(any(kernel = "default", kernel = "avx2")), allow(dead_code))]
+impl Bitmap {
+    /// The binary operation used when no vectorized kernel is available.
+    #[inline]
+    pub(crate) fn binary_fallback(&mut self, other: &Self, op: Op) {
+        if HARDWARE_POPCOUNT {
+            self.binary_with(other, op, Scalar);
+        } else {
+            self.binary_with(other, op, Swar);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn swar() {
+        assert_eq!(count_ones(&[]), 0);
+        assert_eq!(count_ones(&[Word::MAX; 8]), 512);
+        assert_eq!(count_ones(&[Word::MAX; 11]), 704);
+        assert_eq!(count_ones(&Bitmap::full().store), u16::MAX as usize + 1);
+
+        let mut bitmap = Bitmap::full();
+        bitmap.intersection_with(&Bitmap::full(), Swar);
+        assert_eq!(bitmap.len(), u16::MAX as usize + 1);
+    }
+
+    proptest! {
+        #[test]
+        fn prop_count_ones(words in prop::collection::vec(any::<Word>(), 0..40)) {
+            let expected: usize = words.iter().map(|word| word.count_ones() as usize).sum();
+            assert_eq!(count_ones(&words), expected);
+        }
+    }
+}
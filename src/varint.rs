@@ -0,0 +1,180 @@
+//! An encoding picking, depending on the density of the bitmap, between its
+//! raw words and its values as varint-encoded deltas, way smaller for the
+//! sparse bitmaps sent on the wire.
+//!
+//! ```text
+//! tag       0 for the raw words, 1 for the deltas
+//! payload   the raw encoding of `Bitmap::to_bytes`, or the gap between every
+//!           value and the previous one (the first one is the value itself)
+//!           as a LEB128 varint: 7 bits per byte, the high bit is set on all
+//!           the bytes but the last one
+//! ```
+
+use crate::{Bitmap, Error};
+
+const RAW_TAG: u8 = 0;
+const DELTAS_TAG: u8 = 1;
+
+/// Number of bytes of the varint encoding of `value`.
+#[inline]
+fn varint_len(value: u16) -> usize {
+    match value {
+        0..0x80 => 1,
+        0x80..0x4000 => 2,
+        _ => 3,
+    }
+}
+
+impl Bitmap {
+    /// Calls `f` on every value with its gap to the previous value.
+    #[inline]
+    fn visit_deltas(&self, mut f: impl FnMut(u16)) {
+        let mut previous = None;
+        self.visit(|value| {
+            f(previous.map_or(value, |previous| value - previous));
+            previous = Some(value);
+        });
+    }
+
+    /// Encode the bitmap with the smallest of the raw and delta-varint
+    /// encodings, see [`Bitmap::from_varint_bytes`] to decode it.
+    pub fn to_varint_bytes(&self) -> Vec<u8> {
+        let mut deltas_len = 0;
+        self.visit_deltas(|delta| deltas_len += varint_len(delta));
+        if deltas_len >= Self::RAW_SIZE {
+            let mut bytes = Vec::with_capacity(1 + Self::RAW_SIZE);
+            bytes.push(RAW_TAG);
+            self.write_to(&mut bytes).unwrap();
+            return bytes;
+        }
+
+        let mut bytes = Vec::with_capacity(1 + deltas_len);
+        bytes.push(DELTAS_TAG);
+        self.visit_deltas(|mut delta| {
+            while delta >= 0x80 {
+                bytes.push(delta as u8 | 0x80);
+                delta >>= 7;
+            }
+            bytes.push(delta as u8);
+        });
+        bytes
+    }
+
+    /// Decode a bitmap previously encoded with [`Bitmap::to_varint_bytes`].
+    /// Fails if the encoding is unknown, a varint is truncated or the values
+    /// aren't strictly increasing.
+    pub fn from_varint_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let (tag, payload) = bytes
+            .split_first()
+            .ok_or(Error::Corrupted("missing the encoding tag"))?;
+        match *tag {
+            RAW_TAG => return Self::from_bytes(payload),
+            DELTAS_TAG => (),
+            tag => return Err(Error::UnknownEncoding(tag)),
+        }
+
+        let mut bitmap = Bitmap::new();
+        let mut next: u32 = 0;
+        let mut bytes = payload.iter();
+        while let Some(&byte) = bytes.next() {
+            let mut delta = (byte & 0x7F) as u32;
+            let mut shift = 7;
+            let mut last = byte;
+            while last & 0x80 != 0 {
+                last = *bytes.next().ok_or(Error::Corrupted("truncated varint"))?;
+                if shift > 14 {
+                    return Err(Error::Corrupted("the varint overflows a u16"));
+                }
+                delta |= ((last & 0x7F) as u32) << shift;
+                shift += 7;
+            }
+            if delta == 0 && !bitmap.is_empty() {
+                return Err(Error::Corrupted("the values aren't strictly increasing"));
+            }
+            // the first delta is the value itself
+            let value = if bitmap.is_empty() {
+                delta
+            } else {
+                next - 1 + delta
+            };
+            let value = u16::try_from(value).map_err(|_| Error::OutOfRange(value))?;
+            bitmap.insert(value);
+            next = value as u32 + 1;
+        }
+        Ok(bitmap)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn varint() {
+        let bitmap = Bitmap::from_iter([0, 1, 200, 20_000, u16::MAX]);
+        let bytes = bitmap.to_varint_bytes();
+        insta::assert_debug_snapshot!(bytes, @r###"
+        [
+            1,
+            0,
+            1,
+            199,
+            1,
+            216,
+            154,
+            1,
+            223,
+            227,
+            2,
+        ]
+        "###);
+        assert_eq!(Bitmap::from_varint_bytes(&bytes).unwrap(), bitmap);
+
+        assert_eq!(Bitmap::new().to_varint_bytes(), [DELTAS_TAG]);
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[DELTAS_TAG]).unwrap(),
+            Bitmap::new()
+        );
+        let full = Bitmap::full().to_varint_bytes();
+        assert_eq!(full.len(), 1 + Bitmap::RAW_SIZE);
+        assert_eq!(full[0], RAW_TAG);
+        assert_eq!(Bitmap::from_varint_bytes(&full).unwrap(), Bitmap::full());
+
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[]),
+            Err(Error::Corrupted("missing the encoding tag"))
+        );
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[2]),
+            Err(Error::UnknownEncoding(2))
+        );
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[DELTAS_TAG, 0x80]),
+            Err(Error::Corrupted("truncated varint"))
+        );
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[DELTAS_TAG, 0x80, 0x80, 0x80, 1]),
+            Err(Error::Corrupted("the varint overflows a u16"))
+        );
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[DELTAS_TAG, 3, 0]),
+            Err(Error::Corrupted("the values aren't strictly increasing"))
+        );
+        assert_eq!(
+            Bitmap::from_varint_bytes(&[DELTAS_TAG, 0xFF, 0xFF, 0x03, 1]),
+            Err(Error::OutOfRange(65536))
+        );
+        assert!(Bitmap::from_varint_bytes(&[RAW_TAG, 0]).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_varint(bitmap in crate::strategies::any()) {
+            let bytes = bitmap.to_varint_bytes();
+            assert!(bytes.len() <= 1 + Bitmap::RAW_SIZE);
+            assert!(bytes.len() <= 1 + 3 * bitmap.len());
+            assert_eq!(Bitmap::from_varint_bytes(&bytes).unwrap(), bitmap);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{Bitmap, Error, Word};
+
+/// A read-only bitmap borrowing its raw encoding (see [`Bitmap::to_bytes`]),
+/// typically from a memory mapped file. Nothing is copied or decoded upfront,
+/// the words are read from the buffer when they're needed.
+#[derive(Clone, Copy)]
+pub struct BitmapView<'a> {
+    bytes: &'a [u8; Bitmap::RAW_SIZE],
+}
+
+impl<'a> BitmapView<'a> {
+    /// Fails if `bytes` isn't exactly [`Bitmap::RAW_SIZE`] long.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        let bytes = bytes.try_into().map_err(|_| Error::InvalidLength {
+            expected: Bitmap::RAW_SIZE,
+            found: bytes.len(),
+        })?;
+        Ok(BitmapView { bytes })
+    }
+
+    /// The underlying raw encoding.
+    #[inline]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    #[inline]
+    fn word(&self, key: usize) -> Word {
+        let size = core::mem::size_of::<Word>();
+        Word::from_le_bytes(self.bytes[key * size..][..size].try_into().unwrap())
+    }
+
+    /// Iterates over the words of the store.
+    #[inline]
+    pub fn words(&self) -> impl Iterator<Item = Word> + 'a {
+        self.bytes
+            .chunks_exact(core::mem::size_of::<Word>())
+            .map(|chunk| Word::from_le_bytes(chunk.try_into().unwrap()))
+    }
+
+    /// Returns `true` if the value is present in the bitmap.
+    #[inline]
+    pub fn contains(&self, value: u16) -> bool {
+        self.word(Bitmap::key(value)) & (1 << Bitmap::bit_offset(value)) != 0
+    }
+
+    /// The length isn't part of the encoding, thus it must be computed by
+    /// counting the bits of every word.
+    pub fn len(&self) -> usize {
+        self.words().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.words().all(|word| word == 0)
+    }
+
+    /// Copy the view into an owned bitmap.
+    pub fn to_bitmap(&self) -> Bitmap {
+        let mut bitmap = Bitmap::new();
+        for (word, view) in bitmap.store.iter_mut().zip(self.words()) {
+            *word = view;
+        }
+        bitmap.recompute_len();
+        bitmap
+    }
+}
+
+impl PartialEq<Bitmap> for BitmapView<'_> {
+    fn eq(&self, other: &Bitmap) -> bool {
+        self.words().eq(other.store.iter().copied())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn view() {
+        let bitmap = Bitmap::from_iter([0, 1, 63, 64, 1000, u16::MAX]);
+        let bytes = bitmap.to_bytes();
+        let view = BitmapView::new(&bytes).unwrap();
+
+        assert_eq!(view.len(), 6);
+        assert!(!view.is_empty());
+        assert!(view.contains(63));
+        assert!(view.contains(u16::MAX));
+        assert!(!view.contains(62));
+        assert!(view == bitmap);
+        assert_eq!(view.to_bitmap(), bitmap);
+
+        assert_eq!(
+            BitmapView::new(&bytes[1..]).err(),
+            Some(Error::InvalidLength {
+                expected: Bitmap::RAW_SIZE,
+                found: Bitmap::RAW_SIZE - 1
+            })
+        );
+    }
+}